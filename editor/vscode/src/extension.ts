@@ -77,6 +77,26 @@ export async function activate(context: vscode.ExtensionContext) {
         scheme: "file",
         language: "mc-blockstate",
       },
+      {
+        scheme: "file",
+        language: "mc-texture-meta",
+      },
+      {
+        scheme: "file",
+        language: "mc-pack-meta",
+      },
+      {
+        scheme: "mclsp",
+        language: "mc-model",
+      },
+      {
+        scheme: "mclsp",
+        language: "mc-blockstate",
+      },
+      {
+        scheme: "mclsp",
+        language: "mc-texture-meta",
+      },
     ],
     outputChannel: vscode.window.createOutputChannel("MC LSP"),
     revealOutputChannelOn: RevealOutputChannelOn.Info,
@@ -90,6 +110,19 @@ export async function activate(context: vscode.ExtensionContext) {
     clientOptions,
   );
 
+  // Files within jars use the `mclsp:` scheme, and are read from the server.
+  context.subscriptions.push(
+    vscode.workspace.registerTextDocumentContentProvider("mclsp", {
+      async provideTextDocumentContent(uri: vscode.Uri) {
+        const res: any = await client.sendRequest("mc-lsp/readVirtualFile", {
+          uri: uri.toString(),
+        });
+
+        return res?.text ?? "";
+      },
+    }),
+  );
+
   await client.start();
 }
 
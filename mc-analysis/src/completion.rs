@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use mc_hir::{blockstate, model, HirDatabase};
+use mc_hir::{blockstate, forge, model, HirDatabase};
 use mc_source::{FileLocation, FileType, Path, ResolvedPath};
 use mc_syntax::{
   ast::{self, AstNode},
@@ -22,6 +22,7 @@ pub enum CompletionKind {
   Namespace,
   Model,
   Texture,
+  Property,
 }
 
 struct Completer {
@@ -40,12 +41,14 @@ enum PrefixPath {
 pub fn completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Completion> {
   match db.file_type(pos.file) {
     FileType::Model => model_completions(db, pos),
+    FileType::Blockstate if db.is_forge_blockstate(pos.file) => forge_completions(db, pos),
     FileType::Blockstate => blockstate_completions(db, pos),
+    FileType::Texture | FileType::TextureMeta | FileType::PackMeta => vec![],
   }
 }
 
 pub fn model_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Completion> {
-  let keyword_completions = model_keyword_completions(db, pos);
+  let keyword_completions = keyword_completions(db, pos, |path| to_strings(model_keywords(path)));
 
   let Some(node) = db.model_node_at_index(pos) else { return keyword_completions };
   let model = db.parse_model(pos.file);
@@ -97,11 +100,58 @@ pub fn model_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Complet
   completer.completions
 }
 
-fn model_keyword_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Completion> {
+fn model_keywords(path: &[&str]) -> &'static [&'static str] {
+  match path {
+    [] => &["parent", "textures", "elements", "display"],
+    ["elements"] => &["name", "from", "to", "rotation", "faces"],
+    ["elements", "rotation"] => &["origin", "axis", "angle", "rescale"],
+    ["elements", "faces"] => &["north", "south", "east", "west", "up", "down"],
+    ["elements", "faces", _] => &["uv", "texture", "cullface", "rotation", "tintindex"],
+    ["textures"] => &["particle"],
+    ["display"] => &[
+      "thirdperson_righthand",
+      "thirdperson_lefthand",
+      "firstperson_righthand",
+      "firstperson_lefthand",
+      "head",
+      "gui",
+      "ground",
+      "fixed",
+    ],
+    ["display", _] => &["rotation", "translation", "scale"],
+    _ => &[],
+  }
+}
+
+fn blockstate_keywords(path: &[&str]) -> &'static [&'static str] {
+  match path {
+    [] => &["variants", "multipart"],
+    ["variants", _] | ["multipart", "apply"] => &["model", "x", "y", "uvlock", "weight"],
+    ["multipart"] => &["when", "apply"],
+    _ => &[],
+  }
+}
+
+fn forge_keywords(path: &[&str]) -> &'static [&'static str] {
+  match path {
+    [] => &["forge_marker", "defaults", "variants"],
+    ["defaults"] | ["variants", _] | ["variants", _, _] => {
+      &["model", "textures", "x", "y", "uvlock", "weight", "submodel", "transform", "custom"]
+    }
+    _ => &[],
+  }
+}
+
+fn to_strings(keywords: &[&str]) -> Vec<String> { keywords.iter().map(|s| s.to_string()).collect() }
+
+fn keyword_completions(
+  db: &dyn HirDatabase,
+  pos: FileLocation,
+  keywords: impl Fn(&[&str]) -> Vec<String>,
+) -> Vec<Completion> {
   let token = mc_hir::token_at_offset(db, pos);
 
   let Some(parent) = token.parent() else { return vec![] };
-  dbg!(&parent);
 
   // Exclude keys that have already been defined.
   let mut exclude = HashSet::new();
@@ -134,18 +184,10 @@ fn model_keyword_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Com
   }
 
   let strs = path.iter().map(|s| s.as_str()).collect::<Vec<_>>();
-  let keywords: &[&str] = match strs.as_slice() {
-    [] => &["parent", "textures", "elements"],
-    ["elements"] => &["from", "to", "rotation", "faces"],
-    ["elements", "faces"] => &["north", "south", "east", "west", "up", "down"],
-    ["elements", "faces", _] => &["uv", "texture", "tintindex"],
-    ["textures"] => &["particle"],
-    _ => &[],
-  };
 
-  keywords
-    .iter()
-    .filter(|&&k| !exclude.contains(k))
+  keywords(&strs)
+    .into_iter()
+    .filter(|k| !exclude.contains(k))
     .map(|s| Completion {
       label:       format!("\"{}\"", s),
       kind:        CompletionKind::Namespace,
@@ -167,17 +209,103 @@ fn model_keyword_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Com
 }
 
 pub fn blockstate_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Completion> {
-  let Some(node) = db.blockstate_node_at_index(pos) else { return vec![] };
+  let keyword_completions =
+    keyword_completions(db, pos, |path| to_strings(blockstate_keywords(path)));
+
+  let Some(node) = db.blockstate_node_at_index(pos) else { return keyword_completions };
   let blockstate = db.parse_blockstate(pos.file);
 
   let mut completer = Completer::new_blockstate(db, pos, &blockstate);
+  completer.completions.extend(keyword_completions);
 
   match blockstate.nodes[node] {
     blockstate::Node::Model(_) => {
       for n in db.workspace().namespaces.iter() {
         for f in n.files.iter() {
           if let Some(ResolvedPath::Model(mut path)) = f.resolved_path() {
-            // Blockstates implicitly add the 'block' element at the root of the path.
+            // Before 1.13, blockstates implicitly add the 'block' element at the root of
+            // the path.
+            if blockstate::model_prefix(db).is_some() {
+              path.path.segments.remove(0);
+            }
+            completer.complete_path(&path.path, CompletionKind::Model);
+          }
+        }
+      }
+    }
+
+    blockstate::Node::Prop(ref prop) => {
+      // Only complete values, not keys.
+      let ast = db.parse_json(pos.file);
+      let (_, source_map, _) = db.parse_blockstate_with_source_map(pos.file);
+      let element = source_map.props[&node].tree(&ast);
+      if !element.value().is_some_and(|v| v.syntax().text_range().contains_inclusive(pos.index)) {
+        return completer.completions;
+      }
+
+      // Suggest every value this property has elsewhere in the blockstate.
+      let mut values = HashSet::new();
+      for node in blockstate.nodes.values() {
+        match node {
+          blockstate::Node::Prop(other) if other.key == prop.key => {
+            values.extend(other.values.iter().cloned());
+          }
+          blockstate::Node::Variant(variant) => {
+            for p in variant.name.split(',') {
+              if let Some(value) = p.strip_prefix(&format!("{}=", prop.key)) {
+                values.insert(value.to_string());
+              }
+            }
+          }
+          _ => {}
+        }
+      }
+
+      let mut values = values.into_iter().filter(|v| !v.is_empty()).collect::<Vec<_>>();
+      values.sort();
+      for value in values {
+        completer.completions.push(Completion {
+          label:       value.clone(),
+          kind:        CompletionKind::Property,
+          description: format!("{}={}", prop.key, value),
+          retrigger:   false,
+          insert:      value,
+        });
+      }
+    }
+
+    _ => {}
+  }
+
+  completer.completions
+}
+
+pub fn forge_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Completion> {
+  let blockstate = db.parse_forge_blockstate(pos.file);
+
+  let keyword_completions = keyword_completions(db, pos, |path| match path {
+    // Suggest the expanded states, so that they can be overridden.
+    ["variants"] => {
+      let mut names = blockstate.expand().into_iter().map(|s| s.name).collect::<Vec<_>>();
+      if !names.iter().any(|n| n == "inventory") {
+        names.push("inventory".to_string());
+      }
+      names
+    }
+    _ => to_strings(forge_keywords(path)),
+  });
+
+  let Some(node) = db.forge_node_at_index(pos) else { return keyword_completions };
+
+  let mut completer = Completer::new_forge(db, pos, &blockstate);
+  completer.completions.extend(keyword_completions);
+
+  match blockstate.nodes[node] {
+    forge::Node::Model(_) => {
+      for n in db.workspace().namespaces.iter() {
+        for f in n.files.iter() {
+          if let Some(ResolvedPath::Model(mut path)) = f.resolved_path() {
+            // Like vanilla blockstates, these paths are relative to `models/block`.
             path.path.segments.remove(0);
             completer.complete_path(&path.path, CompletionKind::Model);
           }
@@ -185,6 +313,16 @@ pub fn blockstate_completions(db: &dyn HirDatabase, pos: FileLocation) -> Vec<Co
       }
     }
 
+    forge::Node::TextureDef(_) => {
+      for n in db.workspace().namespaces.iter() {
+        for f in n.files.iter() {
+          if let Some(ResolvedPath::Texture(path)) = f.resolved_path() {
+            completer.complete_path(&path.path, CompletionKind::Texture);
+          }
+        }
+      }
+    }
+
     _ => {}
   }
 
@@ -243,6 +381,42 @@ impl Completer {
     Completer::new(current_path, pos)
   }
 
+  pub fn new_forge(
+    db: &dyn HirDatabase,
+    pos: FileLocation,
+    blockstate: &forge::ForgeBlockstate,
+  ) -> Completer {
+    let node = db.forge_node_at_index(pos).unwrap();
+    let mut current_path = None;
+
+    let ast = db.parse_json(pos.file);
+    let (_, source_map, _) = db.parse_forge_blockstate_with_source_map(pos.file);
+
+    match blockstate.nodes[node] {
+      forge::Node::Model(ref m) => {
+        let node = source_map.models[&node].to_node(&ast);
+
+        current_path = Some((m.path.clone(), node))
+      }
+      forge::Node::TextureDef(ref t) => {
+        let element = source_map.texture_defs[&node].tree(&ast);
+
+        // Only complete the value, not the texture variable.
+        if let Some(value) = element.value() {
+          if value.syntax().text_range().contains_inclusive(pos.index) {
+            if let Ok(path) = t.value.parse() {
+              current_path = Some((path, value.syntax().clone()));
+            }
+          }
+        }
+      }
+
+      _ => {}
+    }
+
+    Completer::new(current_path, pos)
+  }
+
   fn new(current_path: Option<(Path, SyntaxNode)>, pos: FileLocation) -> Completer {
     let current_path = current_path.map(|(_, node)| {
       // This is the location of the cursor within the path. The `-1` removes the
@@ -1,19 +1,38 @@
-use std::{fmt, sync::Arc};
+use std::{fmt, panic::RefUnwindSafe, sync::Arc};
 
 use line_index::LineIndex;
-use mc_source::FileId;
+use mc_source::{FileId, FileLoader};
 
 #[salsa::database(
   mc_source::SourceDatabaseStorage,
   mc_hir::HirDatabaseStorage,
   LineIndexDatabaseStorage
 )]
-#[derive(Default)]
 pub struct RootDatabase {
   pub(crate) storage: salsa::Storage<Self>,
+  pub(crate) loader:  Arc<dyn FileLoader + Send + Sync>,
 }
 impl salsa::Database for RootDatabase {}
 
+impl Default for RootDatabase {
+  fn default() -> Self { RootDatabase { storage: Default::default(), loader: Arc::new(NoFiles) } }
+}
+
+// Queries are unwound when they're cancelled. Loaders only read files, so
+// there's nothing for them to leave half updated.
+impl RefUnwindSafe for RootDatabase {}
+
+impl FileLoader for RootDatabase {
+  fn load_bytes(&self, file_id: FileId) -> Arc<[u8]> { self.loader.load_bytes(file_id) }
+}
+
+/// A loader with no files, which is used until the real loader is set.
+struct NoFiles;
+
+impl FileLoader for NoFiles {
+  fn load_bytes(&self, _: FileId) -> Arc<[u8]> { Arc::from([]) }
+}
+
 impl fmt::Debug for RootDatabase {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     f.debug_struct("RootDatabase").finish()
@@ -1,9 +1,9 @@
 use mc_hir::{
   blockstate::{self, PropIter},
-  model, HirDatabase,
+  forge, model, HirDatabase,
 };
 use mc_source::{FileId, TextRange, TextSize};
-use mc_syntax::ast::AstNode;
+use mc_syntax::{ast::AstNode, SyntaxNode};
 
 #[derive(Debug, Clone)]
 pub struct Highlight {
@@ -60,7 +60,11 @@ impl Highlight {
 
     match db.file_type(file) {
       mc_source::FileType::Model => hl.highlight_model(),
+      mc_source::FileType::Blockstate if db.is_forge_blockstate(file) => hl.highlight_forge(),
       mc_source::FileType::Blockstate => hl.highlight_blockstate(),
+      mc_source::FileType::Texture
+      | mc_source::FileType::TextureMeta
+      | mc_source::FileType::PackMeta => {}
     }
 
     hl.hl.tokens.sort_by_key(|t| t.range.start());
@@ -110,22 +114,30 @@ impl Highlighter<'_> {
       match node {
         blockstate::Node::Variant(ref v) => {
           let syntax = source_map.variants[&id].to_node(&ast);
+          self.highlight_prop_list(&v.name, &syntax);
+        }
 
-          for (text, range) in PropIter::new(&v.name, &syntax) {
-            let lhs = text.split('=').next().unwrap();
-            let rhs = text.split('=').nth(1).unwrap_or("");
+        blockstate::Node::Prop(_) => {
+          let element = source_map.props[&id].tree(&ast);
 
+          if let Some(key) = element.key() {
+            // Skip the quotes, to match the keys in variants.
+            let range = key.syntax().text_range();
             self.highlight_range(
-              TextRange::new(range.start(), range.start() + TextSize::from(lhs.len() as u32)),
+              TextRange::new(range.start() + TextSize::from(1), range.end() - TextSize::from(1)),
               HighlightKind::Variable,
             );
-            self.highlight_range(
-              TextRange::new(range.end() - TextSize::from(rhs.len() as u32), range.end()),
-              HighlightKind::Number,
-            );
+          }
+          if let Some(value) = element.value() {
+            for (_, range) in blockstate::condition_values(&value) {
+              self.highlight_range(range, HighlightKind::Number);
+            }
           }
         }
 
+        // The model is highlighted by its own node.
+        blockstate::Node::Multipart(_) | blockstate::Node::Choice(_) => {}
+
         blockstate::Node::Model(_) => {
           self.highlight(source_map.models[&id].tree(&ast), HighlightKind::Model);
         }
@@ -133,11 +145,66 @@ impl Highlighter<'_> {
     }
   }
 
+  fn highlight_forge(&mut self) {
+    let ast = self.db.parse_json(self.file);
+    let (blockstate, source_map, _) = self.db.parse_forge_blockstate_with_source_map(self.file);
+
+    for (id, node) in blockstate.nodes.iter() {
+      match node {
+        forge::Node::Model(_) => {
+          self.highlight(source_map.models[&id].tree(&ast), HighlightKind::Model);
+        }
+        forge::Node::TextureDef(_) => {
+          let element = source_map.texture_defs[&id].tree(&ast);
+
+          if let Some(key) = element.key() {
+            self.highlight(key, HighlightKind::Variable);
+          }
+          if let Some(value) = element.value() {
+            self.highlight(value, HighlightKind::Texture);
+          }
+        }
+        forge::Node::Variant(_) => {}
+      }
+    }
+
+    // Highlight properties the same way as vanilla variants.
+    for ptr in source_map.props.iter() {
+      let Some(key) = ptr.tree(&ast).key() else { continue };
+      self.highlight(key, HighlightKind::Variable);
+    }
+    for ptr in source_map.prop_values.values() {
+      let Some(key) = ptr.tree(&ast).key() else { continue };
+      self.highlight(key, HighlightKind::Number);
+    }
+    for (full, ptr) in blockstate.full.iter().zip(source_map.full.iter()) {
+      let Some(key) = ptr.tree(&ast).key() else { continue };
+      self.highlight_prop_list(&full.name, key.syntax());
+    }
+  }
+
   fn highlight<T: AstNode>(&mut self, node: T, kind: HighlightKind) {
     let range = node.syntax().text_range();
     self.highlight_range(range, kind);
   }
 
+  /// Highlights a list of properties, like `facing=north,half=top`.
+  fn highlight_prop_list(&mut self, s: &str, syntax: &SyntaxNode) {
+    for (text, range) in PropIter::new(s, syntax) {
+      let lhs = text.split('=').next().unwrap();
+      let rhs = text.split('=').nth(1).unwrap_or("");
+
+      self.highlight_range(
+        TextRange::new(range.start(), range.start() + TextSize::from(lhs.len() as u32)),
+        HighlightKind::Variable,
+      );
+      self.highlight_range(
+        TextRange::new(range.end() - TextSize::from(rhs.len() as u32), range.end()),
+        HighlightKind::Number,
+      );
+    }
+  }
+
   fn highlight_range(&mut self, range: TextRange, kind: HighlightKind) {
     self.hl.tokens.push(HighlightToken { range, kind, modifierst: 0 });
   }
@@ -0,0 +1,213 @@
+use mc_hir::{blockstate, model, HirDatabase};
+use mc_source::{FileId, FileLocation, FileType, TextRange, TexturePath};
+use mc_syntax::{
+  ast::{self, AstNode},
+  AstPtr, SyntaxToken,
+};
+
+#[derive(Debug, Clone)]
+pub struct Hover {
+  pub range:    TextRange,
+  /// The contents of the hover, in markdown.
+  pub contents: String,
+}
+
+/// Textures larger than this aren't previewed, to keep hovers small.
+const MAX_PREVIEW_BYTES: usize = 64 * 1024;
+
+pub fn hover(db: &dyn HirDatabase, pos: FileLocation) -> Option<Hover> {
+  match db.file_type(pos.file) {
+    FileType::Model => model_hover(db, pos),
+    FileType::Blockstate if db.is_forge_blockstate(pos.file) => None,
+    FileType::Blockstate => blockstate_hover(db, pos),
+    FileType::Texture | FileType::TextureMeta | FileType::PackMeta => None,
+  }
+}
+
+fn model_hover(db: &dyn HirDatabase, pos: FileLocation) -> Option<Hover> {
+  let token = mc_hir::token_at_offset(db, pos);
+
+  if let Some(hover) = face_hover(db, pos, &token) {
+    return Some(hover);
+  }
+
+  let value = token.parent_ancestors().find_map(ast::Value::cast)?;
+  let range = value.syntax().text_range();
+  let ptr = AstPtr::new(&value);
+  let (model, source_map, _) = db.parse_model_with_source_map(pos.file);
+
+  if source_map.parent.values().any(|p| *p == ptr) {
+    return Some(Hover { range, contents: ancestry_hover(db, pos.file) });
+  }
+
+  if let Some((&id, _)) = source_map.textures.iter().find(|(_, p)| **p == ptr) {
+    let model::Node::Texture(model::Texture::Reference(ref name)) = model.nodes[id] else {
+      return None;
+    };
+    return Some(Hover { range, contents: variable_hover(db, pos.file, name) });
+  }
+
+  let element = value.syntax().parent().and_then(ast::Element::cast)?;
+  let ptr = AstPtr::new(&element);
+  let (&id, _) = source_map.texture_defs.iter().find(|(_, p)| **p == ptr)?;
+  let model::Node::TextureDef(ref def) = model.nodes[id] else { return None };
+  let contents = match def.value.strip_prefix('#') {
+    Some(name) => variable_hover(db, pos.file, name),
+    None => texture_hover(db, &def.value),
+  };
+
+  Some(Hover { range, contents })
+}
+
+/// Faces are hovered on their direction, like `"north"`.
+fn face_hover(db: &dyn HirDatabase, pos: FileLocation, token: &SyntaxToken) -> Option<Hover> {
+  let (model, source_map, _) = db.parse_model_with_source_map(pos.file);
+
+  let element = token.parent_ancestors().find_map(ast::Element::cast)?;
+  let key = element.key()?;
+  let range = key.syntax().text_range();
+  if !range.contains_range(token.text_range()) {
+    return None;
+  }
+
+  let ast::Value::Object(obj) = element.value()? else { return None };
+  let ptr = AstPtr::new(&obj);
+  let (&id, _) = source_map.faces.iter().find(|(_, p)| **p == ptr)?;
+  let model::Node::Face(ref face) = model.nodes[id] else { return None };
+
+  // Explicit UVs are already visible in the source, so only derived UVs are
+  // shown.
+  let model::FaceUv::Derived(uv) = face.uv else { return None };
+  let uv = uv.map(|c| c.0.to_string()).join(", ");
+
+  Some(Hover {
+    range,
+    contents: format!("`uv`: `[{uv}]`\n\nDerived from the element's `from` and `to`."),
+  })
+}
+
+/// Lists the parents of a model, starting with its direct parent.
+fn ancestry_hover(db: &dyn HirDatabase, file: FileId) -> String {
+  let ancestry = db.model_ancestry(file);
+  let parents = ancestry.iter().rev().skip(1).collect::<Vec<_>>();
+  if parents.is_empty() {
+    return "Parent model not found.".into();
+  }
+
+  let mut contents = "Inherits from:\n".to_string();
+  for &parent in parents {
+    contents += &format!("- `{}`\n", model::model_name(db, parent));
+  }
+  contents.pop();
+  contents
+}
+
+/// Shows the texture that `#name` resolves to, as seen from `file`.
+fn variable_hover(db: &dyn HirDatabase, file: FileId, name: &str) -> String {
+  let chain = model::resolve_texture_var(db, file, name);
+  let (last, defined_in) = chain.vars.last().unwrap();
+
+  let Some(ref texture) = chain.texture else {
+    return match defined_in {
+      Some(_) => format!("`#{last}` refers to itself."),
+      None => format!("`#{last}` isn't defined by this model or its parents."),
+    };
+  };
+  let defined_in = defined_in.unwrap();
+
+  let names = chain.vars.iter().map(|(n, _)| format!("`#{n}`")).collect::<Vec<_>>().join(" → ");
+  let (path, preview) = texture_contents(db, texture);
+  let mut contents = format!("{names}: {path}\n\n");
+  if defined_in == file {
+    contents += "Defined in this model.";
+  } else {
+    contents += &format!("Defined in `{}`.", model::model_name(db, defined_in));
+  }
+  if let Some(preview) = preview {
+    contents += &format!("\n\n{preview}");
+  }
+  contents
+}
+
+/// Shows a texture path, along with a preview of the texture.
+fn texture_hover(db: &dyn HirDatabase, value: &str) -> String {
+  match texture_contents(db, value) {
+    (path, Some(preview)) => format!("{path}\n\n{preview}"),
+    (path, None) => path,
+  }
+}
+
+/// Returns a texture path with its size, and an image of the texture, if it
+/// exists.
+fn texture_contents(db: &dyn HirDatabase, value: &str) -> (String, Option<String>) {
+  let Ok(path) = value.parse() else { return (format!("`{value}`"), None) };
+  let Some(texture) = db.lookup_texture(TexturePath::new(path)) else {
+    return (format!("`{value}` (not found)"), None);
+  };
+  let Some(header) = db.png_header(texture) else { return (format!("`{value}`"), None) };
+
+  let bytes = db.file_bytes(texture);
+  let preview = (bytes.len() <= MAX_PREVIEW_BYTES)
+    .then(|| format!("![{value}](data:image/png;base64,{})", base64(&bytes)));
+  (format!("`{value}` ({}×{})", header.width, header.height), preview)
+}
+
+/// Shows the properties of a variant, and the models it uses.
+fn blockstate_hover(db: &dyn HirDatabase, pos: FileLocation) -> Option<Hover> {
+  let token = mc_hir::token_at_offset(db, pos);
+  let (blockstate, source_map, _) = db.parse_blockstate_with_source_map(pos.file);
+
+  let element = token.parent_ancestors().find_map(ast::Element::cast)?;
+  let key = element.key()?;
+  let range = key.syntax().text_range();
+  if !range.contains_range(token.text_range()) {
+    return None;
+  }
+  let id = *source_map.ast_variants.get(&AstPtr::new(&element))?;
+  let blockstate::Node::Variant(ref variant) = blockstate.nodes[id] else { return None };
+
+  let props = variant.name.split(',').filter_map(|p| p.split_once('=')).collect::<Vec<_>>();
+  let mut contents = if props.is_empty() {
+    "No properties.\n".to_string()
+  } else {
+    let mut contents = "Properties:\n".to_string();
+    for (key, value) in props {
+      contents += &format!("- `{key}`: `{value}`\n");
+    }
+    contents
+  };
+
+  let workspace = db.workspace();
+  contents += "\nModels:\n";
+  for &choice in &variant.choices {
+    let blockstate::Node::Choice(ref choice) = blockstate.nodes[choice] else { continue };
+    let blockstate::Node::Model(ref model) = blockstate.nodes[choice.model] else { continue };
+
+    let path = blockstate::model_path(db, &model.path);
+    contents += &match db.lookup_model(path.clone()).and_then(|f| workspace.file(f)) {
+      Some((_, file)) => format!("- `{}` (`{}`)\n", path.path, file.path),
+      None => format!("- `{}` (not found)\n", path.path),
+    };
+  }
+
+  contents.pop();
+  Some(Hover { range, contents })
+}
+
+/// Encodes bytes as standard base64, with padding.
+pub(crate) fn base64(bytes: &[u8]) -> String {
+  const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+  let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+  for chunk in bytes.chunks(3) {
+    let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - i * 8));
+    for i in 0..4 {
+      if i <= chunk.len() {
+        out.push(ALPHABET[(n >> (18 - i * 6)) as usize & 63] as char);
+      } else {
+        out.push('=');
+      }
+    }
+  }
+  out
+}
@@ -1,5 +1,8 @@
 pub mod completion;
 pub mod highlight;
+pub mod hover;
+pub mod rename;
+pub mod symbol;
 
 mod database;
 
@@ -10,19 +13,31 @@ mod tests;
 #[macro_use]
 extern crate log;
 
-use std::{collections::HashMap, panic::UnwindSafe, sync::Arc};
+use std::{
+  collections::HashMap,
+  panic::UnwindSafe,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
+};
 
 use completion::Completion;
 use database::{LineIndexDatabase, RootDatabase};
 use highlight::Highlight;
+use hover::Hover;
 use line_index::LineIndex;
 use mc_hir::{diagnostic::Diagnostics, model, HirDatabase};
 use mc_source::{
-  FileId, FileLocation, FileRange, FileType, SourceDatabase, TextRange, TextSize, Workspace,
+  FileId, FileLoader, FileLocation, FileRange, FileType, SourceDatabase, TextRange, TextSize,
+  Workspace,
 };
-use salsa::{Cancelled, ParallelDatabase};
+use rename::{FileRename, RenameError, SourceChange};
+use salsa::ParallelDatabase;
+use symbol::DocumentSymbol;
 
 pub use mc_hir::diagnostic;
+pub use salsa::Cancelled;
 
 pub struct AnalysisHost {
   db: RootDatabase,
@@ -46,6 +61,11 @@ impl AnalysisHost {
     AnalysisHost { db }
   }
 
+  /// Sets the loader that textures are read from.
+  pub fn set_loader(&mut self, loader: Arc<dyn FileLoader + Send + Sync>) {
+    self.db.loader = loader;
+  }
+
   pub fn snapshot(&self) -> Analysis { Analysis { db: self.db.snapshot() } }
 
   pub fn set_workspace(&mut self, workspace: mc_source::Workspace) {
@@ -58,6 +78,16 @@ impl AnalysisHost {
     self.db.set_file_type(file, ty);
     self.db.set_file_text(file, content.into());
   }
+  /// Adds a texture, or marks it as changed. Textures are binary, so they
+  /// don't go through `add_file` or `change`, and their contents are read with
+  /// the loader once they're needed.
+  pub fn set_texture(&mut self, file: FileId) {
+    // Any value works, as long as it's different from the last one.
+    static REVISION: AtomicU64 = AtomicU64::new(0);
+
+    self.db.set_file_type(file, FileType::Texture);
+    self.db.set_file_revision(file, REVISION.fetch_add(1, Ordering::Relaxed));
+  }
   pub fn change(&mut self, change: Change) {
     self.db.set_file_text(change.file, change.text.into());
   }
@@ -70,7 +100,10 @@ pub struct Change {
 
 impl ParallelDatabase for RootDatabase {
   fn snapshot(&self) -> salsa::Snapshot<Self> {
-    salsa::Snapshot::new(RootDatabase { storage: self.storage.snapshot() })
+    salsa::Snapshot::new(RootDatabase {
+      storage: self.storage.snapshot(),
+      loader:  self.loader.clone(),
+    })
   }
 }
 
@@ -81,6 +114,10 @@ impl Analysis {
   pub fn diagnostics(&self, file: FileId) -> Cancellable<Diagnostics> {
     self.with_db(|db| {
       let mut diagnostics = Diagnostics::new();
+      if !db.file_type(file).is_json() {
+        return diagnostics;
+      }
+
       let parse = db.parse_json(file);
       for error in parse.errors() {
         diagnostics.error(
@@ -91,7 +128,25 @@ impl Analysis {
 
       match db.file_type(file) {
         FileType::Model => diagnostics.extend(&db.validate_model(file)),
+        FileType::Blockstate if db.is_forge_blockstate(file) => {
+          diagnostics.extend(&db.validate_forge_blockstate(file))
+        }
         FileType::Blockstate => diagnostics.extend(&db.validate_blockstate(file)),
+        FileType::TextureMeta => diagnostics.extend(&db.validate_texture_meta(file)),
+        FileType::PackMeta => diagnostics.extend(&db.validate_pack_meta(file)),
+        FileType::Texture => {}
+      }
+
+      // Check if this file shadows a file in a lower layer.
+      let layers = db.file_layers(file);
+      if let Some(&shadowed) = layers.iter().skip_while(|&&f| f != file).nth(1) {
+        let workspace = db.workspace();
+        if let Some((namespace, f)) = workspace.file(shadowed) {
+          diagnostics.info(
+            diagnostic::first_char(TextRange::default()),
+            format!("overrides `{}` from {}", f.path.to_extended_string(), namespace.layer),
+          );
+        }
       }
 
       diagnostics
@@ -102,15 +157,83 @@ impl Analysis {
     self.with_db(|db| Highlight::from_ast(db, file))
   }
 
+  pub fn document_symbols(&self, file: FileId) -> Cancellable<Vec<DocumentSymbol>> {
+    self.with_db(|db| symbol::document_symbols(db, file))
+  }
+
+  /// Fuzzy searches every model, blockstate, and texture in the workspace.
+  pub fn workspace_symbols(
+    &self,
+    query: &str,
+    limit: usize,
+  ) -> Cancellable<Vec<mc_hir::symbol::Symbol>> {
+    self.with_db(|db| db.symbol_index().search(query, limit).into_iter().cloned().collect())
+  }
+
+  pub fn hover(&self, pos: FileLocation) -> Cancellable<Option<Hover>> {
+    self.with_db(|db| hover::hover(db, pos))
+  }
+
   pub fn definition_for_name(&self, pos: FileLocation) -> Cancellable<Option<FileRange>> {
-    self.with_db(|db| match db.file_type(pos.file) {
-      FileType::Model => db.model_def_at_index(pos),
-      FileType::Blockstate => db.blockstate_def_at_index(pos),
+    self.with_db(|db| definition_for_name(db, pos))
+  }
+
+  /// Like `definition_for_name`, but if the definition is a whole file, this
+  /// returns every layer's copy of that file, starting with the one in use.
+  pub fn definitions_for_name(&self, pos: FileLocation) -> Cancellable<Vec<FileRange>> {
+    self.with_db(|db| match definition_for_name(db, pos) {
+      Some(def) if def.range.is_none() => {
+        db.file_layers(def.file).into_iter().map(|file| FileRange { file, range: None }).collect()
+      }
+      Some(def) => vec![def],
+      None => vec![],
     })
   }
 
-  pub fn references_for_name(&self, _: FileLocation) -> Cancellable<Vec<FileRange>> {
-    self.with_db(|_| vec![])
+  /// Returns every reference to the definition at the given position. The
+  /// position may be on a reference, or on the definition of a texture
+  /// variable.
+  pub fn references_for_name(&self, pos: FileLocation) -> Cancellable<Vec<FileRange>> {
+    self.with_db(|db| {
+      let index = db.reference_index();
+
+      let mut defs = index.definitions_at(pos.file, pos.index);
+      if defs.is_empty() {
+        defs.extend(texture_def_at(db, pos));
+      }
+
+      let mut refs = vec![];
+      for def in defs {
+        for &r in index.references(def) {
+          if !refs.contains(&r) {
+            refs.push(r);
+          }
+        }
+      }
+      refs
+    })
+  }
+
+  /// Returns the range of the texture variable to rename, and its name.
+  pub fn prepare_rename(
+    &self,
+    pos: FileLocation,
+  ) -> Cancellable<Result<(TextRange, String), RenameError>> {
+    self.with_db(|db| rename::prepare_rename(db, pos))
+  }
+
+  pub fn rename(
+    &self,
+    pos: FileLocation,
+    new_name: &str,
+  ) -> Cancellable<Result<SourceChange, RenameError>> {
+    self.with_db(|db| rename::rename(db, pos, new_name))
+  }
+
+  /// Returns the edits needed to keep references to `file` working once it is
+  /// moved to `new_path`.
+  pub fn rename_file(&self, file: FileId, new_path: &mc_source::Path) -> Cancellable<FileRename> {
+    self.with_db(|db| rename::rename_file(db, file, new_path))
   }
 
   pub fn line_index(&self, file: FileId) -> Cancellable<Arc<LineIndex>> {
@@ -119,21 +242,12 @@ impl Analysis {
 
   pub fn canonical_model(&self, file: FileId) -> Cancellable<mc_message::Model> {
     self.with_db(|db| {
-      let mut models = vec![];
-      // Recurse child-up.
-      let mut m = db.parse_model(file);
-      loop {
-        let Some(parent) = m.parent.as_ref() else { break };
-        let Some(id) = db.lookup_model(parent.clone()) else { break };
-        models.push(m);
-        m = db.parse_model(id);
-      }
-      models.push(m);
-
-      let mut model = mc_message::Model { elements: vec![] };
+      let mut model = mc_message::Model { elements: vec![], display: Default::default() };
 
       // Recurse parent-down.
-      for m in models.into_iter().rev() {
+      for file in db.model_ancestry(file) {
+        let m = db.parse_model(file);
+
         // If this child defines elements, overwrite all of them.
         if m.nodes.values().any(|n| matches!(n, mc_hir::model::Node::Element(_))) {
           model.elements.clear();
@@ -148,6 +262,48 @@ impl Analysis {
           }
         }
 
+        // Each display context is inherited separately.
+        for &id in &m.display {
+          let model::Node::Transform(ref t) = m.nodes[id] else { continue };
+          let transform = Some(t.clone().into_hir(&m));
+          match t.context {
+            model::DisplayContext::ThirdpersonRighthand => {
+              model.display.thirdperson_righthand = transform
+            }
+            model::DisplayContext::ThirdpersonLefthand => {
+              model.display.thirdperson_lefthand = transform
+            }
+            model::DisplayContext::FirstpersonRighthand => {
+              model.display.firstperson_righthand = transform
+            }
+            model::DisplayContext::FirstpersonLefthand => {
+              model.display.firstperson_lefthand = transform
+            }
+            model::DisplayContext::Head => model.display.head = transform,
+            model::DisplayContext::Gui => model.display.gui = transform,
+            model::DisplayContext::Ground => model.display.ground = transform,
+            model::DisplayContext::Fixed => model.display.fixed = transform,
+          }
+        }
+
+        // Like vanilla, a model that only sets the right hand uses the same
+        // transform for the left hand, rather than inheriting the left hand.
+        let defines = |context: model::DisplayContext| {
+          m.display
+            .iter()
+            .any(|&id| matches!(m.nodes[id], model::Node::Transform(ref t) if t.context == context))
+        };
+        if defines(model::DisplayContext::ThirdpersonRighthand)
+          && !defines(model::DisplayContext::ThirdpersonLefthand)
+        {
+          model.display.thirdperson_lefthand = model.display.thirdperson_righthand.clone();
+        }
+        if defines(model::DisplayContext::FirstpersonRighthand)
+          && !defines(model::DisplayContext::FirstpersonLefthand)
+        {
+          model.display.firstperson_lefthand = model.display.firstperson_righthand.clone();
+        }
+
         let mut texture_map = HashMap::<String, String>::new();
         for node in m.nodes.values() {
           match node {
@@ -191,6 +347,29 @@ impl Analysis {
   }
 }
 
+fn definition_for_name(db: &RootDatabase, pos: FileLocation) -> Option<FileRange> {
+  match db.file_type(pos.file) {
+    FileType::Model => db.model_def_at_index(pos),
+    FileType::Blockstate if db.is_forge_blockstate(pos.file) => db.forge_def_at_index(pos),
+    FileType::Blockstate => db.blockstate_def_at_index(pos),
+    FileType::Texture | FileType::TextureMeta | FileType::PackMeta => None,
+  }
+}
+
+/// Returns the texture variable defined at the given position.
+fn texture_def_at(db: &RootDatabase, pos: FileLocation) -> Option<FileRange> {
+  if db.file_type(pos.file) != FileType::Model {
+    return None;
+  }
+
+  let node = db.model_node_at_index(pos)?;
+  let (_, source_map, _) = db.parse_model_with_source_map(pos.file);
+  let ptr = source_map.texture_defs.get(&node)?;
+  let range = ptr.to_node(&db.parse_json(pos.file)).text_range();
+
+  Some(FileRange { file: pos.file, range: Some(range) })
+}
+
 trait FromHir<T>
 where
   Self: Sized,
@@ -216,7 +395,24 @@ impl FromHir<model::Element> for mc_message::Element {
       from:     hir.from.into_hir(model),
       to:       hir.to.into_hir(model),
       faces:    hir.faces.into_hir(model),
-      rotation: None, // FIXME
+      rotation: hir.rotation.map(|id| match model.nodes[id] {
+        model::Node::Rotation(ref r) => r.clone().into_hir(model),
+        _ => unreachable!(),
+      }),
+    }
+  }
+}
+impl FromHir<model::Rotation> for mc_message::Rotation {
+  fn from_hir(hir: model::Rotation, model: &model::Model) -> Self {
+    mc_message::Rotation {
+      origin:  hir.origin.into_hir(model),
+      axis:    match hir.axis {
+        model::Axis::X => mc_message::Axis::X,
+        model::Axis::Y => mc_message::Axis::Y,
+        model::Axis::Z => mc_message::Axis::Z,
+      },
+      angle:   hir.angle.into(),
+      rescale: hir.rescale,
     }
   }
 }
@@ -244,11 +440,37 @@ impl FromHir<model::Faces> for mc_message::Faces {
 impl FromHir<model::Face> for mc_message::Face {
   fn from_hir(hir: model::Face, model: &model::Model) -> Self {
     mc_message::Face {
-      uv:      [hir.uv[0].into(), hir.uv[1].into(), hir.uv[2].into(), hir.uv[3].into()],
-      texture: match model.nodes[hir.texture] {
+      uv:        hir.uv.coords().map(|c| c.into()),
+      texture:   match model.nodes[hir.texture] {
         model::Node::Texture(model::Texture::Reference(ref t)) => Some(format!("#{t}")),
         _ => unreachable!(),
       },
+      rotation:  hir.rotation,
+      tintindex: hir.tintindex,
+      cullface:  hir.cullface.map(|c| c.into_hir(model)),
+    }
+  }
+}
+
+impl FromHir<model::Direction> for mc_message::Direction {
+  fn from_hir(hir: model::Direction, _: &model::Model) -> Self {
+    match hir {
+      model::Direction::North => mc_message::Direction::North,
+      model::Direction::East => mc_message::Direction::East,
+      model::Direction::South => mc_message::Direction::South,
+      model::Direction::West => mc_message::Direction::West,
+      model::Direction::Up => mc_message::Direction::Up,
+      model::Direction::Down => mc_message::Direction::Down,
+    }
+  }
+}
+
+impl FromHir<model::Transform> for mc_message::Transform {
+  fn from_hir(hir: model::Transform, model: &model::Model) -> Self {
+    mc_message::Transform {
+      rotation:    hir.rotation.into_hir(model),
+      translation: hir.translation.into_hir(model),
+      scale:       hir.scale.into_hir(model),
     }
   }
 }
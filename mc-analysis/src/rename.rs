@@ -0,0 +1,378 @@
+//! Renaming texture variables and moving files.
+
+use std::fmt;
+
+use mc_hir::{blockstate, model, HirDatabase};
+use mc_source::{
+  FileId, FileLocation, FileRange, FileType, Layer, ModelPath, Path, ResolvedPath, TextRange,
+  TextSize, TexturePath,
+};
+use mc_syntax::{
+  ast::{self, AstNode},
+  AstPtr,
+};
+
+/// A change to the text of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+  pub file:     FileId,
+  pub range:    TextRange,
+  pub new_text: String,
+}
+
+/// The changes made by a rename.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceChange {
+  pub edits: Vec<TextEdit>,
+  /// Files to move, along with their new path.
+  pub moves: Vec<(FileId, Path)>,
+}
+
+/// The edits needed to keep references working when a file is moved.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileRename {
+  pub edits:  Vec<TextEdit>,
+  /// References that can't be updated, as their file can't refer to the new
+  /// path. These will stop resolving once the file is moved.
+  pub errors: Vec<RenameError>,
+}
+
+/// The reason a rename can't be done, which is shown to the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameError(pub String);
+
+impl fmt::Display for RenameError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str(&self.0) }
+}
+
+impl std::error::Error for RenameError {}
+
+/// A texture variable under the cursor.
+struct Variable {
+  /// The range of the name, without quotes or a leading `#`.
+  range: TextRange,
+  name:  String,
+  /// The definitions the cursor refers to. A use in a parent model may refer
+  /// to a definition in each child.
+  defs:  Vec<FileRange>,
+}
+
+/// Returns the range of the name that would be renamed, along with the name.
+/// This is either a texture variable, or a path to a model or texture.
+pub fn prepare_rename(
+  db: &dyn HirDatabase,
+  pos: FileLocation,
+) -> Result<(TextRange, String), RenameError> {
+  if let Some((range, written, _)) = path_at(db, pos) {
+    return Ok((range, written));
+  }
+
+  let var = variable_at(db, pos)?;
+  Ok((var.range, var.name))
+}
+
+/// Renames the texture variable or path at the given position. For texture
+/// variables, every model that shares the variable, through a parent or a
+/// child that overrides it, is changed. For paths, the file is moved, and every
+/// reference to it is changed.
+pub fn rename(
+  db: &dyn HirDatabase,
+  pos: FileLocation,
+  new_name: &str,
+) -> Result<SourceChange, RenameError> {
+  if let Some((_, written, file)) = path_at(db, pos) {
+    return rename_path(db, pos, file, &written, new_name);
+  }
+
+  let var = variable_at(db, pos)?;
+
+  // Accept `#wall`, as that's how uses are written.
+  let new_name = new_name.strip_prefix('#').unwrap_or(new_name);
+  if new_name.is_empty() {
+    return Err(RenameError("texture variable names can't be empty".into()));
+  }
+  if let Some(c) = new_name.chars().find(|&c| c == '"' || c == '\\' || c.is_control()) {
+    return Err(RenameError(format!(
+      "texture variable names can't contain `{}`",
+      c.escape_debug()
+    )));
+  }
+
+  let (defs, uses) = variable_family(db, var.defs);
+
+  let mut edits = vec![];
+  for def in defs {
+    let Some(range) = def_name_range(db, def) else { continue };
+    edits.push(TextEdit { file: def.file, range, new_text: new_name.into() });
+  }
+  for r in uses {
+    let text = db.file_text(r.file);
+    let Some((range, _)) = name_range(&text, r.range.unwrap(), "#") else { continue };
+    edits.push(TextEdit { file: r.file, range, new_text: new_name.into() });
+  }
+
+  // Models from dependencies or the vanilla jar can't be edited, so renaming
+  // would leave them using the old name.
+  let workspace = db.workspace();
+  for namespace in workspace.namespaces.iter().filter(|n| n.layer != Layer::Project) {
+    if let Some(file) = namespace.files.iter().find(|f| edits.iter().any(|e| e.file == f.id)) {
+      return Err(RenameError(format!(
+        "`#{}` is also used by `{}`, which isn't part of the project",
+        var.name, file.path
+      )));
+    }
+  }
+
+  edits.sort_by_key(|e| (e.file, e.range.start()));
+  edits.dedup();
+  Ok(SourceChange { edits, moves: vec![] })
+}
+
+/// Moves the file referred to by the path at `pos`, so that the path reads
+/// `new_name`.
+fn rename_path(
+  db: &dyn HirDatabase,
+  pos: FileLocation,
+  file: FileId,
+  written: &str,
+  new_name: &str,
+) -> Result<SourceChange, RenameError> {
+  let workspace = db.workspace();
+  if let Some(namespace) =
+    workspace.namespaces.iter().find(|n| n.files.iter().any(|f| f.id == file))
+  {
+    if namespace.layer != Layer::Project {
+      return Err(RenameError(format!("`{written}` isn't part of the project")));
+    }
+  }
+
+  let invalid = || RenameError(format!("`{new_name}` isn't a valid path"));
+  let mut path = new_name.parse::<Path>().map_err(|_| invalid())?;
+  if path.segments.iter().any(|s| s.is_empty() || s.contains(['"', '\\'])) {
+    return Err(invalid());
+  }
+  if let Some(prefix) = path_prefix(db, pos.file, db.file_type(file)) {
+    path.segments.insert(0, prefix.into());
+  }
+
+  let new_path = match db.file_type(file) {
+    FileType::Model => ModelPath::new(path).file_path(),
+    FileType::Texture => TexturePath::new(path).file_path(),
+    _ => return Err(invalid()),
+  };
+  if !db.lookup_path(new_path.clone()).is_empty() {
+    return Err(RenameError(format!("`{new_name}` already exists")));
+  }
+
+  let rename = rename_file(db, file, &new_path);
+  if let Some(error) = rename.errors.into_iter().next() {
+    return Err(error);
+  }
+
+  Ok(SourceChange { edits: rename.edits, moves: vec![(file, new_path)] })
+}
+
+/// Returns the edits needed to keep every reference to `file` working once it
+/// is moved to `new_path`. References keep the style they were written in, so
+/// a namespace is only added when it is needed.
+pub fn rename_file(db: &dyn HirDatabase, file: FileId, new_path: &Path) -> FileRename {
+  let path = match (db.file_type(file), ResolvedPath::parse(new_path)) {
+    (FileType::Model, Some(ResolvedPath::Model(m))) => m.path,
+    (FileType::Texture, Some(ResolvedPath::Texture(t))) => t.path,
+    // Nothing can refer to the file at its new path.
+    _ => return FileRename::default(),
+  };
+
+  let workspace = db.workspace();
+  let is_project = |file: FileId| {
+    workspace
+      .namespaces
+      .iter()
+      .any(|n| n.layer == Layer::Project && n.files.iter().any(|f| f.id == file))
+  };
+
+  let mut rename = FileRename::default();
+  for r in db.reference_index().references(FileRange { file, range: None }) {
+    // Files outside the project can't be changed.
+    if !is_project(r.file) {
+      continue;
+    }
+
+    let text = db.file_text(r.file);
+    let Some((range, written)) = name_range(&text, r.range.unwrap(), "") else { continue };
+
+    let mut path = path.clone();
+    if let Some(prefix) = path_prefix(db, r.file, db.file_type(file)) {
+      // Paths are always inside the prefix, so the new path can't be written.
+      if path.segments.len() < 2 || path.segments[0] != prefix {
+        let name = workspace.file(r.file).map(|(_, f)| f.path.to_string()).unwrap_or_default();
+        rename.errors.push(RenameError(format!(
+          "`{name}` can only refer to models in `{prefix}/`, so it can't refer to `{}`",
+          path.to_extended_string()
+        )));
+        continue;
+      }
+      path.segments.remove(0);
+    }
+
+    let new_text = if written.contains(':') { path.to_extended_string() } else { path.to_string() };
+    rename.edits.push(TextEdit { file: r.file, range, new_text });
+  }
+
+  rename.edits.sort_by_key(|e| (e.file, e.range.start()));
+  rename
+}
+
+/// Returns the path to a model or texture at the given position, as it's
+/// written, along with the range of the path and the file it refers to.
+fn path_at(db: &dyn HirDatabase, pos: FileLocation) -> Option<(TextRange, String, FileId)> {
+  let def = db
+    .reference_index()
+    .definitions_at(pos.file, pos.index)
+    .into_iter()
+    .find(|d| d.range.is_none())?;
+
+  let token = mc_hir::token_at_offset(db, pos);
+  let value = token.parent_ancestors().find_map(ast::Value::cast)?;
+  let (range, written) = name_range(&db.file_text(pos.file), value.syntax().text_range(), "")?;
+  Some((range, written, def.file))
+}
+
+/// The directory that paths to files of type `target` are implicitly in, when
+/// they're written in `file`.
+fn path_prefix(db: &dyn HirDatabase, file: FileId, target: FileType) -> Option<&'static str> {
+  match (db.file_type(file), target) {
+    (FileType::Blockstate, FileType::Model) if db.is_forge_blockstate(file) => Some("block"),
+    (FileType::Blockstate, FileType::Model) => blockstate::model_prefix(db),
+    _ => None,
+  }
+}
+
+fn variable_at(db: &dyn HirDatabase, pos: FileLocation) -> Result<Variable, RenameError> {
+  let not_found = || RenameError("no texture variable to rename here".into());
+
+  if db.file_type(pos.file) != FileType::Model {
+    return Err(not_found());
+  }
+
+  let token = mc_hir::token_at_offset(db, pos);
+  let text = db.file_text(pos.file);
+
+  // Definitions are renamed from their key, like `"side"` in `textures`.
+  if let Some(element) = token.parent_ancestors().find_map(ast::Element::cast) {
+    if let Some(key) =
+      element.key().filter(|k| k.syntax().text_range().contains_range(token.text_range()))
+    {
+      let (_, source_map, _) = db.parse_model_with_source_map(pos.file);
+      let ptr = AstPtr::new(&element);
+      if source_map.texture_defs.values().any(|p| *p == ptr) {
+        let (range, name) =
+          name_range(&text, key.syntax().text_range(), "").ok_or_else(not_found)?;
+        let def = FileRange { file: pos.file, range: Some(element.syntax().text_range()) };
+        return Ok(Variable { range, name, defs: vec![def] });
+      }
+    }
+  }
+
+  // Uses are anything that resolves to a definition, like `"#side"`.
+  let value = token.parent_ancestors().find_map(ast::Value::cast).ok_or_else(not_found)?;
+  let (range, name) = name_range(&text, value.syntax().text_range(), "#").ok_or_else(not_found)?;
+  let defs = db
+    .reference_index()
+    .definitions_at(pos.file, pos.index)
+    .into_iter()
+    .filter(|d| d.range.is_some())
+    .collect::<Vec<_>>();
+  if defs.is_empty() {
+    return Err(RenameError(format!("texture `#{name}` is not defined")));
+  }
+
+  Ok(Variable { range, name, defs })
+}
+
+/// Finds every definition and use of the same variable as `defs`. Uses link to
+/// the definitions they resolve to, and definitions link to definitions of the
+/// same name in parents and children.
+fn variable_family(
+  db: &dyn HirDatabase,
+  mut defs: Vec<FileRange>,
+) -> (Vec<FileRange>, Vec<FileRange>) {
+  let index = db.reference_index();
+  let mut uses = vec![];
+
+  let mut i = 0;
+  while i < defs.len() {
+    let def = defs[i];
+    i += 1;
+
+    let mut linked = overriding_defs(db, def);
+    for &r in index.references(def) {
+      if !uses.contains(&r) {
+        uses.push(r);
+        linked.extend(index.definitions_at(r.file, r.range.unwrap().start()));
+      }
+    }
+
+    for d in linked {
+      if d.range.is_some() && !defs.contains(&d) {
+        defs.push(d);
+      }
+    }
+  }
+
+  (defs, uses)
+}
+
+/// Returns the definitions with the same name as `def` in the ancestors and
+/// descendants of its model.
+fn overriding_defs(db: &dyn HirDatabase, def: FileRange) -> Vec<FileRange> {
+  let Some((_, name)) = def_element(db, def) else { return vec![] };
+
+  let ancestry = db.model_ancestry(def.file);
+  let descendants = db.model_descendants(def.file);
+  ancestry
+    .iter()
+    .chain(descendants.iter())
+    .filter_map(|&file| {
+      let (model, source_map, _) = db.parse_model_with_source_map(file);
+      let id = model.texture_def(&name)?;
+      let range = source_map.texture_defs[&id].to_node(&db.parse_json(file)).text_range();
+      Some(FileRange { file, range: Some(range) })
+    })
+    .collect()
+}
+
+/// Returns the element and the name of the texture definition that `def`
+/// points to.
+fn def_element(db: &dyn HirDatabase, def: FileRange) -> Option<(ast::Element, String)> {
+  let json = db.parse_json(def.file);
+  let (model, source_map, _) = db.parse_model_with_source_map(def.file);
+
+  source_map.texture_defs.iter().find_map(|(&id, ptr)| {
+    let element = ptr.tree(&json);
+    if Some(element.syntax().text_range()) != def.range {
+      return None;
+    }
+    let model::Node::TextureDef(ref d) = model.nodes[id] else { return None };
+    Some((element, d.name.clone()))
+  })
+}
+
+fn def_name_range(db: &dyn HirDatabase, def: FileRange) -> Option<TextRange> {
+  let (element, _) = def_element(db, def)?;
+  let text = db.file_text(def.file);
+  name_range(&text, element.key()?.syntax().text_range(), "").map(|(range, _)| range)
+}
+
+/// Returns the range of a name inside a string, skipping the quotes and
+/// `prefix`. Strings with escapes are skipped, as the name wouldn't line up
+/// with the source.
+fn name_range(text: &str, range: TextRange, prefix: &str) -> Option<(TextRange, String)> {
+  let name = text[range].strip_prefix('"')?.strip_suffix('"')?.strip_prefix(prefix)?;
+  if name.contains('\\') {
+    return None;
+  }
+
+  let start = range.start() + TextSize::of('"') + TextSize::of(prefix);
+  Some((TextRange::at(start, TextSize::of(name)), name.to_string()))
+}
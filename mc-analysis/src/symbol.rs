@@ -0,0 +1,261 @@
+//! The outline of a file, for `textDocument/documentSymbol`.
+
+use mc_hir::{blockstate, forge, model, HirDatabase};
+use mc_source::{FileId, FileType, TextRange};
+use mc_syntax::ast::{self, AstNode};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSymbol {
+  pub name:            String,
+  pub detail:          Option<String>,
+  pub kind:            SymbolKind,
+  /// The range of the whole symbol.
+  pub range:           TextRange,
+  /// The range to select when the symbol is picked, like the key of an
+  /// element. This is always within `range`.
+  pub selection_range: TextRange,
+  pub children:        Vec<DocumentSymbol>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+  Model,
+  Texture,
+  Element,
+  Face,
+  Property,
+  Variant,
+  Multipart,
+}
+
+impl DocumentSymbol {
+  fn new(name: impl Into<String>, kind: SymbolKind, range: TextRange) -> Self {
+    DocumentSymbol {
+      name: name.into(),
+      detail: None,
+      kind,
+      range,
+      selection_range: range,
+      children: vec![],
+    }
+  }
+
+  /// Creates a symbol for an element in an object, which is selected by its
+  /// key.
+  fn for_element(name: impl Into<String>, kind: SymbolKind, element: &ast::Element) -> Self {
+    let range = element.syntax().text_range();
+    DocumentSymbol {
+      selection_range: element.key().map(|k| k.syntax().text_range()).unwrap_or(range),
+      ..DocumentSymbol::new(name, kind, range)
+    }
+  }
+
+  fn detail(mut self, detail: impl Into<String>) -> Self {
+    self.detail = Some(detail.into());
+    self
+  }
+}
+
+pub fn document_symbols(db: &dyn HirDatabase, file: FileId) -> Vec<DocumentSymbol> {
+  match db.file_type(file) {
+    FileType::Model => model_symbols(db, file),
+    FileType::Blockstate if db.is_forge_blockstate(file) => forge_symbols(db, file),
+    FileType::Blockstate => blockstate_symbols(db, file),
+    FileType::Texture | FileType::TextureMeta | FileType::PackMeta => vec![],
+  }
+}
+
+fn model_symbols(db: &dyn HirDatabase, file: FileId) -> Vec<DocumentSymbol> {
+  let json = db.parse_json(file);
+  let (model, source_map, _) = db.parse_model_with_source_map(file);
+
+  let mut symbols = vec![];
+  for (id, node) in model.nodes.iter() {
+    match node {
+      model::Node::Parent(parent) => {
+        let range = source_map.parent[&id].to_node(&json).text_range();
+        symbols.push(
+          DocumentSymbol::new(parent.path.path.to_string(), SymbolKind::Model, range)
+            .detail("parent"),
+        );
+      }
+      model::Node::TextureDef(def) => {
+        let element = source_map.texture_defs[&id].tree(&json);
+        symbols.push(
+          DocumentSymbol::for_element(format!("#{}", def.name), SymbolKind::Texture, &element)
+            .detail(&def.value),
+        );
+      }
+      model::Node::Element(element) => {
+        let range = source_map.elements[&id].to_node(&json).text_range();
+        let bounds = format!("{} → {}", pos(&element.from), pos(&element.to));
+
+        let mut symbol = match element.name {
+          Some(ref name) => DocumentSymbol::new(name, SymbolKind::Element, range).detail(bounds),
+          None => DocumentSymbol::new(bounds, SymbolKind::Element, range),
+        };
+        for (dir, face) in element.faces.iter() {
+          let Some(face_element) = source_map.faces[&face].to_node(&json).parent() else {
+            continue;
+          };
+          let Some(face_element) = ast::Element::cast(face_element) else { continue };
+
+          let mut face_symbol =
+            DocumentSymbol::for_element(dir.name(), SymbolKind::Face, &face_element);
+          if let model::Node::Face(ref face) = model.nodes[face] {
+            if let model::Node::Texture(model::Texture::Reference(ref name)) =
+              model.nodes[face.texture]
+            {
+              face_symbol = face_symbol.detail(format!("#{name}"));
+            }
+          }
+          symbol.children.push(face_symbol);
+        }
+        symbols.push(symbol);
+      }
+      _ => {}
+    }
+  }
+
+  // Nodes are allocated in the order they're parsed, but keys may be written
+  // in any order.
+  symbols.sort_by_key(|s| s.range.start());
+  symbols
+}
+
+fn pos(pos: &model::Pos) -> String { format!("[{}, {}, {}]", pos.x.0, pos.y.0, pos.z.0) }
+
+fn blockstate_symbols(db: &dyn HirDatabase, file: FileId) -> Vec<DocumentSymbol> {
+  let json = db.parse_json(file);
+  let (blockstate, source_map, _) = db.parse_blockstate_with_source_map(file);
+
+  let models = |choices: &[blockstate::NodeId]| {
+    choices
+      .iter()
+      .filter_map(|&id| {
+        let blockstate::Node::Choice(ref choice) = blockstate.nodes[id] else { return None };
+        let blockstate::Node::Model(ref model) = blockstate.nodes[choice.model] else {
+          return None;
+        };
+        let range = source_map.choices[&id].to_node(&json).text_range();
+        Some(DocumentSymbol::new(model.path.to_string(), SymbolKind::Model, range))
+      })
+      .collect::<Vec<_>>()
+  };
+
+  let mut symbols = vec![];
+  for (id, node) in blockstate.nodes.iter() {
+    let symbol = match node {
+      blockstate::Node::Variant(variant) => {
+        let element = source_map.variants[&id].tree(&json);
+        let name = if variant.name.is_empty() { "\"\"" } else { &variant.name };
+        DocumentSymbol {
+          children: models(&variant.choices),
+          ..DocumentSymbol::for_element(name, SymbolKind::Variant, &element)
+        }
+      }
+      blockstate::Node::Multipart(multipart) => {
+        let range = source_map.multiparts[&id].to_node(&json).text_range();
+        let name = match multipart.when {
+          Some(ref when) => format!("when {}", condition(&blockstate, when)),
+          None => "always".to_string(),
+        };
+        DocumentSymbol {
+          children: models(&multipart.apply),
+          ..DocumentSymbol::new(name, SymbolKind::Multipart, range)
+        }
+      }
+      _ => continue,
+    };
+    symbols.push(symbol);
+  }
+
+  symbols.sort_by_key(|s| s.range.start());
+  symbols
+}
+
+/// Writes a multipart condition on one line, like `north=true,up=false`.
+fn condition(blockstate: &blockstate::Blockstate, cond: &blockstate::Condition) -> String {
+  match cond {
+    blockstate::Condition::Or(conditions) => {
+      conditions.iter().map(|c| condition(blockstate, c)).collect::<Vec<_>>().join(" || ")
+    }
+    blockstate::Condition::And(conditions) => {
+      conditions.iter().map(|c| condition(blockstate, c)).collect::<Vec<_>>().join(" && ")
+    }
+    blockstate::Condition::Props(props) => props
+      .iter()
+      .filter_map(|&id| match blockstate.nodes[id] {
+        blockstate::Node::Prop(ref prop) => Some(format!(
+          "{}={}{}",
+          prop.key,
+          if prop.negated { "!" } else { "" },
+          prop.values.join("|")
+        )),
+        _ => None,
+      })
+      .collect::<Vec<_>>()
+      .join(","),
+  }
+}
+
+fn forge_symbols(db: &dyn HirDatabase, file: FileId) -> Vec<DocumentSymbol> {
+  let json = db.parse_json(file);
+  let (blockstate, source_map, _) = db.parse_forge_blockstate_with_source_map(file);
+
+  // The model and textures that a variant sets.
+  let variant_children = |id: forge::NodeId| {
+    let forge::Node::Variant(ref variant) = blockstate.nodes[id] else { return vec![] };
+
+    let mut children = vec![];
+    if let Some(model) = variant.model {
+      if let forge::Node::Model(ref m) = blockstate.nodes[model] {
+        let range = source_map.models[&model].to_node(&json).text_range();
+        children.push(DocumentSymbol::new(m.path.to_string(), SymbolKind::Model, range));
+      }
+    }
+    for &texture in &variant.textures {
+      if let forge::Node::TextureDef(ref def) = blockstate.nodes[texture] {
+        let element = source_map.texture_defs[&texture].tree(&json);
+        children.push(
+          DocumentSymbol::for_element(format!("#{}", def.name), SymbolKind::Texture, &element)
+            .detail(&def.value),
+        );
+      }
+    }
+    children
+  };
+
+  let mut symbols = vec![];
+  if let Some(defaults) = blockstate.defaults {
+    let range = source_map.variants[&defaults].to_node(&json).text_range();
+    symbols.push(DocumentSymbol {
+      children: variant_children(defaults),
+      ..DocumentSymbol::new("defaults", SymbolKind::Variant, range)
+    });
+  }
+
+  for (prop, ptr) in blockstate.props.iter().zip(&source_map.props) {
+    let mut symbol =
+      DocumentSymbol::for_element(&prop.name, SymbolKind::Property, &ptr.tree(&json));
+    for (value, id) in &prop.values {
+      let Some(element) = source_map.prop_values.get(id) else { continue };
+      symbol.children.push(DocumentSymbol {
+        children: variant_children(*id),
+        ..DocumentSymbol::for_element(value, SymbolKind::Variant, &element.tree(&json))
+      });
+    }
+    symbols.push(symbol);
+  }
+
+  for (full, ptr) in blockstate.full.iter().zip(&source_map.full) {
+    let mut symbol = DocumentSymbol::for_element(&full.name, SymbolKind::Variant, &ptr.tree(&json));
+    for &id in &full.variants {
+      symbol.children.extend(variant_children(id));
+    }
+    symbols.push(symbol);
+  }
+
+  symbols.sort_by_key(|s| s.range.start());
+  symbols
+}
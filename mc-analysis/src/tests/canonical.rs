@@ -0,0 +1,70 @@
+use mc_message::Pos;
+use mc_source::{FileType, SourceDatabase};
+
+use super::add_file;
+use crate::AnalysisHost;
+
+#[test]
+fn inherit_display() {
+  let mut db = super::test_db();
+
+  let parent = add_file(&mut db, 10, "models/item/handheld.json", FileType::Model);
+  db.set_file_text(
+    parent,
+    r#"{
+      "display": {
+        "gui": { "scale": [2, 2, 2] },
+        "head": { "rotation": [0, 180, 0] },
+        "thirdperson_righthand": { "rotation": [0, -90, 55] },
+        "firstperson_righthand": { "rotation": [0, -90, 25] },
+        "firstperson_lefthand": { "rotation": [0, 90, -25] }
+      }
+    }"#
+      .into(),
+  );
+  let child = add_file(&mut db, 11, "models/item/sword.json", FileType::Model);
+  db.set_file_text(
+    child,
+    r#"{
+      "parent": "test:item/handheld",
+      "display": {
+        "gui": { "translation": [1, 0, 0] }
+      }
+    }"#
+      .into(),
+  );
+
+  let model = AnalysisHost { db }.snapshot().canonical_model(child).unwrap();
+  let gui = model.display.gui.unwrap();
+  let head = model.display.head.unwrap();
+
+  // The child's `gui` replaces the parent's, rather than being merged with it.
+  assert_eq!(gui.translation, Pos { x: 1.0, y: 0.0, z: 0.0 });
+  assert_eq!(gui.scale, Pos { x: 1.0, y: 1.0, z: 1.0 });
+  assert_eq!(head.rotation, Pos { x: 0.0, y: 180.0, z: 0.0 });
+  assert_eq!(model.display.ground, None);
+
+  // Left hands fall back to the right hand when the model doesn't set them.
+  let thirdperson_lefthand = model.display.thirdperson_lefthand.unwrap();
+  let firstperson_lefthand = model.display.firstperson_lefthand.unwrap();
+  assert_eq!(thirdperson_lefthand.rotation, Pos { x: 0.0, y: -90.0, z: 55.0 });
+  assert_eq!(firstperson_lefthand.rotation, Pos { x: 0.0, y: 90.0, z: -25.0 });
+}
+
+#[test]
+fn parent_cycle() {
+  let mut db = super::test_db();
+
+  let a = add_file(&mut db, 10, "models/block/a.json", FileType::Model);
+  db.set_file_text(
+    a,
+    r#"{
+      "parent": "test:block/a",
+      "elements": [{ "from": [0, 0, 0], "to": [16, 16, 16] }]
+    }"#
+      .into(),
+  );
+
+  let model = AnalysisHost { db }.snapshot().canonical_model(a).unwrap();
+  assert_eq!(model.elements.len(), 1);
+}
@@ -1,19 +1,30 @@
-use mc_source::{FileLocation, SourceDatabase, TextSize};
+use mc_source::{FileId, FileLocation, FileType, SourceDatabase, TextSize};
 use mc_test::{expect, Expect};
 
 use super::FOO_MODEL;
+use crate::database::RootDatabase;
 
 fn complete(input: &str, expect: Expect) {
+  let db = super::test_db();
+  complete_in(db, FOO_MODEL, input, expect);
+}
+
+fn complete_blockstate(input: &str, expect: Expect) {
   let mut db = super::test_db();
+  let file = super::add_file(&mut db, 10, "blockstates/foo.json", FileType::Blockstate);
+  complete_in(db, file, input, expect);
+}
 
-  let cursor = input.find('|').unwrap();
+fn complete_in(mut db: RootDatabase, file: FileId, input: &str, expect: Expect) {
+  // `|` is used in blockstate conditions, so the cursor is the last one.
+  let cursor = input.rfind('|').unwrap();
   let input = input[..cursor].to_string() + &input[cursor + 1..];
 
-  db.set_file_text(FOO_MODEL, input.into());
+  db.set_file_text(file, input.into());
 
   let completions = crate::completion::completions(
     &db,
-    FileLocation { file: FOO_MODEL, index: TextSize::from(cursor as u32) },
+    FileLocation { file, index: TextSize::from(cursor as u32) },
   );
 
   expect
@@ -67,6 +78,7 @@ fn complete_keys() {
       "parent"    parent
       "textures"  textures
       "elements"  elements
+      "display"   display
     "#],
   );
 
@@ -79,6 +91,7 @@ fn complete_keys() {
       ]
     }"#,
     expect![@r#"
+      "name"      name
       "from"      from
       "to"        to
       "rotation"  rotation
@@ -97,6 +110,7 @@ fn excludes_existing_keys() {
     expect![@r#"
       "parent"    parent
       "textures"  textures
+      "display"   display
     "#],
   );
 
@@ -108,6 +122,59 @@ fn excludes_existing_keys() {
     expect![@r#"
       "parent"    parent
       "textures"  textures
+      "display"   display
+    "#],
+  );
+}
+
+#[test]
+fn complete_multipart() {
+  complete_blockstate(
+    r#"{
+      "multipart": [
+        {
+          |
+        }
+      ]
+    }"#,
+    expect![@r#"
+      "when"   when
+      "apply"  apply
+    "#],
+  );
+
+  complete_blockstate(
+    r#"{
+      "multipart": [
+        { "when": { "north": "low|tall" }, "apply": { "model": "block/foo" } },
+        { "when": { "north": "none|" }, "apply": { "model": "block/foo" } }
+      ]
+    }"#,
+    expect![@r#"
+      low   north=low
+      none  north=none
+      tall  north=tall
+    "#],
+  );
+}
+
+#[test]
+fn complete_forge_states() {
+  complete_blockstate(
+    r#"{
+      "forge_marker": 1,
+      "variants": {
+        "facing": { "north": {}, "south": {} },
+        "lit": { "true": {}, "false": {} },
+        "inventory": [{}],
+        |
+      }
+    }"#,
+    expect![@r#"
+      "facing=north,lit=true"   facing=north,lit=true
+      "facing=south,lit=true"   facing=south,lit=true
+      "facing=north,lit=false"  facing=north,lit=false
+      "facing=south,lit=false"  facing=south,lit=false
     "#],
   );
 }
@@ -0,0 +1,537 @@
+use std::sync::Arc;
+
+use mc_source::{FileId, FileType, SourceDatabase};
+use mc_test::{expect, Expect};
+
+use super::{add_file, set_file_bytes};
+use crate::{database::RootDatabase, Analysis};
+
+/// Builds a PNG with a valid header, and no image data.
+fn png(width: u32, height: u32) -> Vec<u8> {
+  let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+  png.extend_from_slice(&13_u32.to_be_bytes());
+  png.extend_from_slice(b"IHDR");
+  png.extend_from_slice(&width.to_be_bytes());
+  png.extend_from_slice(&height.to_be_bytes());
+  png.extend_from_slice(&[8, 6, 0, 0, 0]);
+  png
+}
+
+fn check(db: RootDatabase, file: FileId, expect: Expect) {
+  let text = db.file_text(file);
+  let analysis = Analysis { db: salsa::ParallelDatabase::snapshot(&db) };
+
+  let mut out = String::new();
+  for d in analysis.diagnostics(file).unwrap().iter() {
+    out.push_str(&format!("{:?} `{}`: {}\n", d.severity, &text[d.span], d.message));
+    for hint in &d.hints {
+      out.push_str(&format!("  hint: {hint}\n"));
+    }
+  }
+
+  expect.assert_eq(&out);
+}
+
+fn texture_meta(width: u32, height: u32, meta: &str, expect: Expect) {
+  let mut db = super::test_db();
+
+  let texture = add_file(&mut db, 10, "textures/block/anim.png", FileType::Texture);
+  set_file_bytes(&mut db, texture, png(width, height));
+
+  let meta_file = add_file(&mut db, 11, "textures/block/anim.png.mcmeta", FileType::TextureMeta);
+  db.set_file_text(meta_file, meta.into());
+
+  check(db, meta_file, expect);
+}
+
+#[test]
+fn texture_meta_frames() {
+  texture_meta(
+    16,
+    48,
+    r#"{
+      "animation": {
+        "frametime": 2,
+        "frames": [0, 2, { "index": 3, "time": 4 }]
+      }
+    }"#,
+    expect![@r#"
+      Error `{ "index": 3, "time": 4 }`: frame index 3 is out of bounds
+        hint: the texture has 3 frames of 16x16
+    "#],
+  );
+}
+
+#[test]
+fn texture_meta_frame_size() {
+  texture_meta(
+    16,
+    40,
+    r#"{
+      "animation": {}
+    }"#,
+    expect![@r#"
+      Error `{`: texture height 40 is not a multiple of the frame height 16
+        hint: the texture is 16x40
+    "#],
+  );
+
+  texture_meta(
+    32,
+    32,
+    r#"{
+      "animation": { "width": 12, "height": 16, "frametime": 0 },
+      "texture": { "blur": true, "clamp": 1 }
+    }"#,
+    expect![@r#"
+      Error `0`: expected a positive integer
+      Error `1`: expected boolean
+      Error `12`: texture width 32 is not a multiple of the frame width 12
+        hint: the texture is 32x32
+    "#],
+  );
+}
+
+#[test]
+fn texture_meta_missing_texture() {
+  let mut db = super::test_db();
+
+  let meta = add_file(&mut db, 11, "textures/block/missing.png.mcmeta", FileType::TextureMeta);
+  db.set_file_text(meta, "{}".into());
+
+  check(
+    db,
+    meta,
+    expect![@r#"
+      Warn `{`: texture `test:textures/block/missing.png` not found
+    "#],
+  );
+}
+
+/// Adds a `pack.mcmeta` to the test database.
+fn add_pack(db: &mut RootDatabase, id: u32, text: &str) -> FileId {
+  let id = FileId::new_raw(id);
+
+  let mut workspace = (*db.workspace()).clone();
+  workspace.packs.push(id);
+  db.set_workspace(Arc::new(workspace));
+  db.set_file_type(id, FileType::PackMeta);
+  db.set_file_text(id, text.into());
+
+  id
+}
+
+#[test]
+fn pack_meta() {
+  let mut db = super::test_db();
+  let pack = add_pack(
+    &mut db,
+    10,
+    r#"{
+      "pack": { "pack_format": 14, "supported_formats": [15, 22] },
+      "overlays": {
+        "entries": [{ "formats": { "min_inclusive": 20 }, "directory": "a/b" }]
+      }
+    }"#,
+  );
+
+  check(
+    db,
+    pack,
+    expect![@r#"
+      Error `{`: missing `description`
+      Error `{`: expected `min_inclusive` and `max_inclusive`
+      Error `"a/b"`: invalid overlay directory `a/b`
+        hint: directories may only contain letters, numbers, `-`, `_`, and `.`
+      Warn `14`: pack format 14 isn't used by any release
+        hint: the closest release is minecraft 1.19.4
+      Warn `[15, 22]`: `supported_formats` is ignored before pack format 18
+        hint: pack format 18 is used by minecraft 1.20.2
+      Error `[15, 22]`: `supported_formats` does not include the pack format 14
+      Warn `{`: overlays are ignored before pack format 18
+        hint: pack format 18 is used by minecraft 1.20.2
+    "#],
+  );
+}
+
+#[test]
+fn blockstate_flattening() {
+  let mut db = super::test_db();
+  add_pack(&mut db, 10, r#"{ "pack": { "pack_format": 4, "description": "" } }"#);
+
+  let blockstate = add_file(&mut db, 11, "blockstates/foo.json", FileType::Blockstate);
+  db.set_file_text(
+    blockstate,
+    r#"{
+      "variants": {
+        "normal": { "model": "test:block/foo" }
+      }
+    }"#
+      .into(),
+  );
+
+  check(
+    db,
+    blockstate,
+    expect![@r#"
+      Error `"normal": { "model": "test:block/foo" }`: `normal` is not a property list
+        hint: since 1.13, use "" for blocks without properties
+    "#],
+  );
+}
+
+#[test]
+fn multipart() {
+  let mut db = super::test_db();
+
+  let blockstate = add_file(&mut db, 10, "blockstates/foo.json", FileType::Blockstate);
+  db.set_file_text(
+    blockstate,
+    r#"{
+      "multipart": [
+        { "apply": { "model": "test:foo" } },
+        { "when": { "north": "true|side|true" }, "apply": { "model": "test:foo", "y": 90 } },
+        { "when": { "OR": [{ "Up": "!x|" }, { "down": true }] }, "apply": {} },
+        { "when": { "AND": [], "north": "side" } },
+        { "when": {}, "apply": { "model": "test:foo" } }
+      ]
+    }"#
+      .into(),
+  );
+
+  check(
+    db,
+    blockstate,
+    expect![@r#"
+      Error `{`: missing `model`
+      Error `"AND"`: `AND` must be the only key in a condition
+      Error `{`: missing `apply`
+      Error `{}`: empty condition
+      Warn `true`: duplicate property value `true`
+      Error `"Up"`: invalid property key `Up`
+        hint: property keys may only contain lowercase letters
+      Error `"!x|"`: invalid empty property value
+    "#],
+  );
+}
+
+#[test]
+fn weighted_variants() {
+  let mut db = super::test_db();
+
+  let blockstate = add_file(&mut db, 10, "blockstates/foo.json", FileType::Blockstate);
+  db.set_file_text(
+    blockstate,
+    r#"{
+      "variants": {
+        "snowy=false": [
+          { "model": "test:foo", "weight": 3 },
+          { "model": "test:foo", "y": 90, "weight": 0 },
+          { "y": 180 }
+        ],
+        "snowy=true": { "model": "test:foo", "weight": 2 }
+      },
+      "multipart": [{ "apply": [] }]
+    }"#
+      .into(),
+  );
+
+  check(
+    db,
+    blockstate,
+    expect![@r#"
+      Error `0`: weight must be a positive integer
+      Error `{`: missing `model`
+      Warn `"weight"`: `weight` is only used when there are multiple models
+        hint: put this model in an array with the other models to pick from
+      Error `[]`: expected at least one model
+    "#],
+  );
+}
+
+#[test]
+fn forge_blockstate() {
+  let mut db = super::test_db();
+
+  let blockstate = add_file(&mut db, 10, "blockstates/foo.json", FileType::Blockstate);
+  db.set_file_text(
+    blockstate,
+    r#"{
+      "forge_marker": 1,
+      "defaults": { "textures": { "all": "test:blocks/foo" } },
+      "variants": {
+        "facing": {
+          "north": { "model": "test:foo" },
+          "south": { "y": 180 }
+        },
+        "Lit": { "true": {}, "false": {} },
+        "facing=south,Lit=true": [{ "model": "test:foo", "weight": 0 }],
+        "facing=up,powered=true": { "model": "test:foo" },
+        "inventory": [{}]
+      }
+    }"#
+      .into(),
+  );
+
+  check(
+    db,
+    blockstate,
+    expect![@r#"
+      Error `0`: weight must be a positive integer
+      Error `"Lit"`: invalid property key `Lit`
+        hint: property keys may only contain lowercase letters
+      Error `facing=up`: property `facing` has no value `up`
+        hint: `facing` may be one of north, south
+      Error `powered=true`: unknown property `powered`
+      Error `{`: no model for `Lit=false,facing=south`
+        hint: set a `model` in `defaults`, or in this variant
+      Error `"inventory"`: no model for `inventory`
+        hint: set a `model` in `defaults`, or in this variant
+    "#],
+  );
+}
+
+#[test]
+fn element_rotation() {
+  let mut db = super::test_db();
+
+  let model = add_file(&mut db, 10, "models/block/slope.json", FileType::Model);
+  db.set_file_text(
+    model,
+    r#"{
+      "elements": [
+        { "from": [0, 0, 0], "to": [16, 16, 16], "rotation": { "origin": [8, 8, 8], "axis": "y", "angle": 22.5 } },
+        { "from": [0, 0, 0], "to": [16, 16, 16], "rotation": { "origin": [8, 8, 8], "axis": "w", "angle": 30 } },
+        { "from": [0, 0, 0], "to": [16, 16, 16], "rotation": { "axis": "x", "angle": -90, "rescale": true } }
+      ]
+    }"#
+    .into(),
+  );
+
+  check(
+    db,
+    model,
+    expect![@r#"
+      Error `"w"`: invalid axis `w`
+        hint: the axis must be `x`, `y`, or `z`
+      Error `30`: invalid rotation angle 30
+        hint: the angle must be between -45 and 45, in steps of 22.5
+      Error `-90`: invalid rotation angle -90
+        hint: the angle must be between -45 and 45, in steps of 22.5
+      Error `{`: missing `origin`
+    "#],
+  );
+}
+
+#[test]
+fn face_properties() {
+  let mut db = super::test_db();
+
+  let model = add_file(&mut db, 10, "models/block/slab.json", FileType::Model);
+  db.set_file_text(
+    model,
+    r##"{
+      "elements": [
+        {
+          "from": [0, 0, 0],
+          "to": [16, 8, 16],
+          "faces": {
+            "down": { "texture": "#bottom", "cullface": "down", "tintindex": 0 },
+            "up": { "texture": "#top", "cullface": "up", "rotation": 90 },
+            "north": { "texture": "#side", "cullface": "south", "rotation": 45 },
+            "east": { "texture": "#side", "cullface": "sideways" }
+          }
+        }
+      ]
+    }"##
+      .into(),
+  );
+
+  check(
+    db,
+    model,
+    expect![@r#"
+      Error `45`: rotation must be 0, 90, 180, or 270
+      Error `"sideways"`: invalid cullface `sideways`
+        hint: the cullface must be `north`, `east`, `south`, `west`, `up`, or `down`
+      Warn `"south"`: cullface `south` doesn't match the side this face is on
+        hint: this face is on the `north` side of the block
+      Warn `"up"`: this face isn't on the edge of the block
+        hint: the face will be hidden whenever the `up` side is covered
+    "#],
+  );
+}
+
+#[test]
+fn display_transforms() {
+  let mut db = super::test_db();
+
+  let model = add_file(&mut db, 10, "models/item/sword.json", FileType::Model);
+  db.set_file_text(
+    model,
+    r#"{
+      "display": {
+        "gui": { "rotation": [30, 225, 0], "translation": [0, 0, 0], "scale": [0.625, 0.625, 0.625] },
+        "ground": { "translation": [0, 100, 0], "scale": [5, 1, 1] },
+        "fixed": { "scale": [1, 1] },
+        "offhand": {}
+      }
+    }"#
+    .into(),
+  );
+
+  check(
+    db,
+    model,
+    expect![@r#"
+      Warn `[0, 100, 0]`: translation is out of range
+        hint: each component is clamped to between -80 and 80
+      Warn `[5, 1, 1]`: scale is out of range
+        hint: each component is clamped to between -4 and 4
+      Error `[1, 1]`: expected 3 elements
+      Warn `"offhand"`: unknown key `"offhand"`
+    "#],
+  );
+}
+
+#[test]
+fn parent_cycle() {
+  let mut db = super::test_db();
+
+  let a = add_file(&mut db, 10, "models/block/a.json", FileType::Model);
+  let b = add_file(&mut db, 11, "models/block/b.json", FileType::Model);
+  let c = add_file(&mut db, 12, "models/block/c.json", FileType::Model);
+  db.set_file_text(a, r#"{ "parent": "test:block/b" }"#.into());
+  db.set_file_text(b, r#"{ "parent": "test:block/a" }"#.into());
+  db.set_file_text(c, r#"{ "parent": "test:block/a" }"#.into());
+
+  assert_eq!(mc_hir::HirDatabase::model_ancestry(&db, a), vec![a]);
+
+  // `c` isn't part of the cycle, so the error is only shown in `a` and `b`.
+  let analysis = Analysis { db: salsa::ParallelDatabase::snapshot(&db) };
+  assert!(analysis.diagnostics(c).unwrap().iter().next().is_none());
+
+  check(
+    db,
+    a,
+    expect![@r#"
+      Error `"test:block/b"`: model inherits from itself: `test:block/a` -> `test:block/b` -> `test:block/a`
+        hint: the parents of this model will be ignored
+    "#],
+  );
+}
+
+#[test]
+fn unresolved_models() {
+  let mut db = super::test_db();
+
+  let model = add_file(&mut db, 10, "models/block/bar.json", FileType::Model);
+  db.set_file_text(model, r#"{ "parent": "test:block/fo" }"#.into());
+  let blockstate = add_file(&mut db, 11, "blockstates/bar.json", FileType::Blockstate);
+  db.set_file_text(
+    blockstate,
+    r#"{
+      "variants": {
+        "normal": [{ "model": "test:foo" }, { "model": "test:bar" }, { "model": "test:fooo" }, { "model": "stone" }]
+      }
+    }"#
+    .into(),
+  );
+
+  let analysis = Analysis { db: salsa::ParallelDatabase::snapshot(&db) };
+  let diagnostics = analysis.diagnostics(model).unwrap();
+  let suggestion = diagnostics.iter().next().unwrap().suggestion.as_ref().unwrap();
+  assert_eq!(suggestion.title, "replace with `test:block/foo`");
+  assert_eq!(suggestion.replace, r#""test:block/foo""#);
+
+  check(
+    db,
+    blockstate,
+    expect![@r#"
+      Error `"test:fooo"`: model `test:fooo` not found
+        hint: did you mean `test:foo`?
+      Error `"stone"`: model `stone` not found
+    "#],
+  );
+}
+
+#[test]
+fn undefined_texture_variables() {
+  let mut db = super::test_db();
+
+  let cube = add_file(&mut db, 10, "models/block/cube.json", FileType::Model);
+  db.set_file_text(
+    cube,
+    r##"{
+      "elements": [{
+        "from": [0, 0, 0],
+        "to": [16, 16, 16],
+        "faces": { "up": { "texture": "#top" }, "north": { "texture": "#side" } }
+      }]
+    }"##
+      .into(),
+  );
+  let log = add_file(&mut db, 11, "models/block/log.json", FileType::Model);
+  db.set_file_text(
+    log,
+    r##"{
+      "parent": "test:block/cube",
+      "textures": { "top": "#side", "side": "test:blocks/bar" }
+    }"##
+      .into(),
+  );
+  let pillar = add_file(&mut db, 12, "models/block/pillar.json", FileType::Model);
+  db.set_file_text(
+    pillar,
+    r#"{ "parent": "test:block/cube", "textures": { "side": "test:blocks/bar" } }"#.into(),
+  );
+  let blockstate = add_file(&mut db, 13, "blockstates/log.json", FileType::Blockstate);
+  db.set_file_text(
+    blockstate,
+    r#"{ "variants": { "axis=x": { "model": "test:log" }, "axis=y": { "model": "test:pillar" } } }"#
+      .into(),
+  );
+
+  // Forge blockstates may define textures for the model they use.
+  let lamp = add_file(&mut db, 16, "models/block/lamp.json", FileType::Model);
+  db.set_file_text(
+    lamp,
+    r#"{ "parent": "test:block/cube", "textures": { "side": "test:blocks/bar" } }"#.into(),
+  );
+  let forge = add_file(&mut db, 14, "blockstates/lamp.json", FileType::Blockstate);
+  db.set_file_text(
+    forge,
+    r#"{
+      "forge_marker": 1,
+      "defaults": { "model": "test:lamp", "textures": { "top": "test:blocks/bar" } },
+      "variants": { "normal": [{}] }
+    }"#
+      .into(),
+  );
+
+  let item = add_file(&mut db, 15, "models/item/lamp.json", FileType::Model);
+  db.set_file_text(
+    item,
+    r##"{
+      "elements": [{ "from": [0, 0, 0], "to": [16, 16, 16], "faces": { "up": { "texture": "#layer0" } } }]
+    }"##
+      .into(),
+  );
+
+  let analysis = Analysis { db: salsa::ParallelDatabase::snapshot(&db) };
+  assert!(analysis.diagnostics(log).unwrap().iter().next().is_none());
+  let item = analysis.diagnostics(item).unwrap();
+  assert_eq!(
+    item.iter().map(|d| d.message.as_str()).collect::<Vec<_>>(),
+    ["texture `#layer0` is not defined"]
+  );
+
+  check(
+    db,
+    cube,
+    expect![@r##"
+      Error `"#top"`: texture `#top` is not defined
+        hint: `test:block/pillar` inherits from this model, but doesn't define `top`
+    "##],
+  );
+}
@@ -0,0 +1,139 @@
+use mc_source::{FileId, FileLocation, FileType, SourceDatabase, TextSize};
+use mc_test::{expect, Expect};
+
+use super::{add_file, set_file_bytes, BAR_TEXTURE, FOO_MODEL};
+use crate::database::RootDatabase;
+
+fn hover(input: &str, expect: Expect) { hover_in(super::test_db(), FOO_MODEL, input, expect) }
+
+/// Hovers at the `|` in the given file.
+fn hover_in(mut db: RootDatabase, file: FileId, input: &str, expect: Expect) {
+  let cursor = input.find('|').unwrap();
+  let input = input[..cursor].to_string() + &input[cursor + 1..];
+  db.set_file_text(file, input.into());
+
+  let hover = crate::hover::hover(&db, FileLocation { file, index: TextSize::from(cursor as u32) });
+
+  expect.assert_eq(&hover.map(|h| h.contents + "\n").unwrap_or_default());
+}
+
+#[test]
+fn hover_derived_uv() {
+  hover(
+    r#"{
+      "elements": [
+        {
+          "from": [2, 0, 4],
+          "to": [14, 8, 12],
+          "faces": {
+            "n|orth": { "texture": "foo" }
+          }
+        }
+      ]
+    }"#,
+    expect![@r#"
+      `uv`: `[2, 8, 14, 16]`
+
+      Derived from the element's `from` and `to`.
+    "#],
+  );
+
+  hover(
+    r#"{
+      "elements": [
+        {
+          "from": [2, 0, 4],
+          "to": [14, 8, 12],
+          "faces": {
+            "n|orth": { "texture": "foo", "uv": [0, 0, 4, 4] }
+          }
+        }
+      ]
+    }"#,
+    expect![@""],
+  );
+}
+
+#[test]
+fn hover_texture_variable() {
+  let mut db = super::test_db();
+
+  // A 2x2 RGBA image. Only the header is needed for the size.
+  let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+  png.extend([0, 0, 0, 2, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+  db.set_file_type(BAR_TEXTURE, FileType::Texture);
+  set_file_bytes(&mut db, BAR_TEXTURE, png);
+
+  let base = add_file(&mut db, 10, "models/block/base.json", FileType::Model);
+  db.set_file_text(base, r##"{ "textures": { "side": "#all" } }"##.into());
+
+  hover_in(
+    db,
+    FOO_MODEL,
+    r##"{
+      "parent": "test:block/base",
+      "textures": { "all": "test:blocks/bar" },
+      "elements": [{ "faces": { "north": { "texture": "#si|de" } } }]
+    }"##,
+    expect![@r#"
+      `#side` → `#all`: `test:blocks/bar` (2×2)
+
+      Defined in this model.
+
+      ![test:blocks/bar](data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAIAAAACCAYAAAA=)
+    "#],
+  );
+}
+
+#[test]
+fn hover_parent() {
+  let mut db = super::test_db();
+
+  let base = add_file(&mut db, 10, "models/block/base.json", FileType::Model);
+  db.set_file_text(base, r#"{ "parent": "block/block" }"#.into());
+
+  hover_in(
+    db,
+    FOO_MODEL,
+    r#"{ "parent": "test:block/ba|se" }"#,
+    expect![@r#"
+      Inherits from:
+      - `test:block/base`
+      - `block/block`
+    "#],
+  );
+}
+
+#[test]
+fn hover_variant() {
+  let mut db = super::test_db();
+
+  let blockstate = add_file(&mut db, 10, "blockstates/foo.json", FileType::Blockstate);
+
+  hover_in(
+    db,
+    blockstate,
+    r#"{
+      "variants": {
+        "facing=north,h|alf=top": [{ "model": "test:foo" }, { "model": "test:missing" }]
+      }
+    }"#,
+    expect![@r#"
+      Properties:
+      - `facing`: `north`
+      - `half`: `top`
+
+      Models:
+      - `test:block/foo` (`test:models/block/foo.json`)
+      - `test:block/missing` (not found)
+    "#],
+  );
+}
+
+#[test]
+fn base64() {
+  assert_eq!(crate::hover::base64(b""), "");
+  assert_eq!(crate::hover::base64(b"f"), "Zg==");
+  assert_eq!(crate::hover::base64(b"fo"), "Zm8=");
+  assert_eq!(crate::hover::base64(b"foobar"), "Zm9vYmFy");
+}
@@ -1,10 +1,16 @@
 use std::sync::Arc;
 
-use mc_source::{File, FileId, FileType, Namespace, SourceDatabase, Workspace};
+use mc_source::{File, FileId, FileLoader, FileType, Layer, Namespace, SourceDatabase, Workspace};
 
 use crate::database::RootDatabase;
 
+mod canonical;
 mod completion;
+mod diagnostics;
+mod hover;
+mod references;
+mod rename;
+mod symbol;
 
 const BLOCK_MODEL: FileId = FileId::new_raw(0);
 const FOO_MODEL: FileId = FileId::new_raw(1);
@@ -14,9 +20,10 @@ pub fn test_db() -> RootDatabase {
   let mut db = RootDatabase::default();
 
   db.set_workspace(Arc::new(Workspace {
-    namespaces: vec![
+    namespaces:        vec![
       Namespace {
         name:  "minecraft".to_string(),
+        layer: Layer::Vanilla,
         files: vec![File {
           id:   BLOCK_MODEL,
           ty:   FileType::Model,
@@ -25,21 +32,24 @@ pub fn test_db() -> RootDatabase {
       },
       Namespace {
         name:  "test".to_string(),
+        layer: Layer::Project,
         files: vec![
           File {
             id:   FOO_MODEL,
             ty:   FileType::Model,
             path: "test:models/block/foo.json".parse().unwrap(),
           },
-          // NB: File text is undefined for this file.
+          // NB: File bytes are undefined for this file.
           File {
             id:   BAR_TEXTURE,
-            ty:   FileType::Model,
+            ty:   FileType::Texture,
             path: "test:textures/blocks/bar.png".parse().unwrap(),
           },
         ],
       },
     ],
+    packs:             vec![],
+    minecraft_version: None,
   }));
 
   db.set_file_text(FileId::new_raw(0), "{}".into());
@@ -51,6 +61,42 @@ pub fn test_db() -> RootDatabase {
   db
 }
 
+/// Adds a file to the `test` namespace of the test database.
+pub fn add_file(db: &mut RootDatabase, id: u32, path: &str, ty: FileType) -> FileId {
+  let id = FileId::new_raw(id);
+
+  let mut workspace = (*db.workspace()).clone();
+  let namespace = workspace.namespaces.iter_mut().find(|n| n.name == "test").unwrap();
+  namespace.files.push(File { id, ty, path: format!("test:{path}").parse().unwrap() });
+  db.set_workspace(Arc::new(workspace));
+  db.set_file_type(id, ty);
+
+  id
+}
+
+/// Sets the contents of a binary file, like a texture.
+pub fn set_file_bytes(db: &mut RootDatabase, file: FileId, bytes: Vec<u8>) {
+  db.loader = Arc::new(WithFile { file, bytes: bytes.into(), inner: db.loader.clone() });
+  db.set_file_revision(file, 0);
+}
+
+/// Loads `bytes` for `file`, and every other file from `inner`.
+struct WithFile {
+  file:  FileId,
+  bytes: Arc<[u8]>,
+  inner: Arc<dyn FileLoader + Send + Sync>,
+}
+
+impl FileLoader for WithFile {
+  fn load_bytes(&self, file_id: FileId) -> Arc<[u8]> {
+    if file_id == self.file {
+      self.bytes.clone()
+    } else {
+      self.inner.load_bytes(file_id)
+    }
+  }
+}
+
 #[test]
 fn test_db_works() {
   let db = test_db();
@@ -61,3 +107,30 @@ fn test_db_works() {
   assert_eq!(db.file_type(FileId::new_raw(0)), FileType::Model);
   assert_eq!(db.file_type(FileId::new_raw(1)), FileType::Model);
 }
+
+#[test]
+fn project_overrides_vanilla() {
+  let mut db = RootDatabase::default();
+
+  let override_model = FileId::new_raw(3);
+  let path: mc_source::Path = "minecraft:models/block/block.json".parse().unwrap();
+  db.set_workspace(Arc::new(Workspace {
+    namespaces:        vec![
+      Namespace {
+        name:  "minecraft".to_string(),
+        layer: Layer::Vanilla,
+        files: vec![File { id: BLOCK_MODEL, ty: FileType::Model, path: path.clone() }],
+      },
+      Namespace {
+        name:  "minecraft".to_string(),
+        layer: Layer::Project,
+        files: vec![File { id: override_model, ty: FileType::Model, path: path.clone() }],
+      },
+    ],
+    packs:             vec![],
+    minecraft_version: None,
+  }));
+
+  assert_eq!(db.lookup_path(path), vec![override_model, BLOCK_MODEL]);
+  assert_eq!(db.file_layers(BLOCK_MODEL), vec![override_model, BLOCK_MODEL]);
+}
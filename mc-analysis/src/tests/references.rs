@@ -0,0 +1,80 @@
+use mc_source::{FileLocation, FileRange, FileType, SourceDatabase, TextSize};
+use mc_test::{expect, Expect};
+
+use super::{add_file, BLOCK_MODEL, FOO_MODEL};
+use crate::{database::RootDatabase, AnalysisHost};
+
+/// Finds the references at the `|` in the given model, and prints each of them
+/// as `path: text`.
+fn references(mut db: RootDatabase, input: &str, expect: Expect) {
+  let cursor = input.find('|').unwrap();
+  let input = input[..cursor].to_string() + &input[cursor + 1..];
+  db.set_file_text(FOO_MODEL, input.into());
+
+  let workspace = db.workspace();
+  let render = |db: &RootDatabase, r: FileRange| {
+    let file = workspace.namespaces.iter().flat_map(|n| &n.files).find(|f| f.id == r.file).unwrap();
+    match r.range {
+      Some(range) => format!("{}: {}\n", file.path, &db.file_text(r.file)[range]),
+      None => format!("{}\n", file.path),
+    }
+  };
+
+  let analysis = AnalysisHost { db }.snapshot();
+  let refs = analysis
+    .references_for_name(FileLocation { file: FOO_MODEL, index: TextSize::from(cursor as u32) })
+    .unwrap();
+
+  let actual = refs.into_iter().map(|r| render(&analysis.db, r)).collect::<String>();
+  expect.assert_eq(&actual);
+}
+
+#[test]
+fn model_references() {
+  let mut db = super::test_db();
+
+  let child = add_file(&mut db, 10, "models/block/child.json", FileType::Model);
+  db.set_file_text(child, r#"{ "parent": "minecraft:block/block" }"#.into());
+  let blockstate = add_file(&mut db, 11, "blockstates/foo.json", FileType::Blockstate);
+  db.set_file_text(blockstate, r#"{ "variants": { "": { "model": "minecraft:block" } } }"#.into());
+
+  references(
+    db,
+    r#"{ "parent": "block/bl|ock" }"#,
+    expect![@r#"
+      test:models/block/foo.json: "block/block"
+      test:models/block/child.json: "minecraft:block/block"
+      test:blockstates/foo.json: "minecraft:block"
+    "#],
+  );
+}
+
+#[test]
+fn texture_variable_references() {
+  let mut db = super::test_db();
+
+  db.set_file_text(
+    BLOCK_MODEL,
+    r##"{
+      "textures": { "particle": "#side" },
+      "elements": [{
+        "from": [0, 0, 0],
+        "to": [16, 16, 16],
+        "faces": { "north": { "texture": "#side" } }
+      }]
+    }"##
+      .into(),
+  );
+
+  references(
+    db,
+    r#"{
+      "parent": "block/block",
+      "textures": { "si|de": "test:blocks/bar" }
+    }"#,
+    expect![@r##"
+      models/block/block.json: "#side"
+      models/block/block.json: "#side"
+    "##],
+  );
+}
@@ -0,0 +1,193 @@
+use mc_source::{FileId, FileLocation, FileType, Path, SourceDatabase, TextSize};
+use mc_test::{expect, Expect};
+
+use super::{add_file, BAR_TEXTURE, BLOCK_MODEL, FOO_MODEL};
+use crate::{database::RootDatabase, rename::TextEdit, AnalysisHost};
+
+/// Renames the name at the `|` in the given model to `new_name`, and prints
+/// each changed file, followed by any moved files.
+fn rename(mut db: RootDatabase, input: &str, new_name: &str, expect: Expect) {
+  let cursor = input.find('|').unwrap();
+  let input = input[..cursor].to_string() + &input[cursor + 1..];
+  db.set_file_text(FOO_MODEL, input.into());
+
+  let analysis = AnalysisHost { db }.snapshot();
+  let change = analysis
+    .rename(FileLocation { file: FOO_MODEL, index: TextSize::from(cursor as u32) }, new_name)
+    .unwrap();
+
+  let actual = match change {
+    Ok(change) => {
+      let mut actual = render_edits(&analysis.db, &change.edits);
+      for (file, path) in change.moves {
+        actual += &format!("moved {} to {}\n", file_path(&analysis.db, file), path);
+      }
+      actual
+    }
+    Err(e) => format!("error: {e}\n"),
+  };
+  expect.assert_eq(&actual);
+}
+
+/// Prints each file changed by `edits`, with the edits applied.
+fn render_edits(db: &RootDatabase, edits: &[TextEdit]) -> String {
+  let mut files = edits.iter().map(|e| e.file).collect::<Vec<_>>();
+  files.dedup();
+
+  let mut actual = String::new();
+  for file in files {
+    let mut text = db.file_text(file).to_string();
+    // Edits are sorted, so apply them from the end to keep earlier ranges valid.
+    for edit in edits.iter().rev().filter(|e| e.file == file) {
+      text.replace_range(std::ops::Range::<usize>::from(edit.range), &edit.new_text);
+    }
+    actual += &format!("{}:\n{text}\n", file_path(db, file));
+  }
+  actual
+}
+
+fn file_path(db: &RootDatabase, file: FileId) -> Path {
+  let workspace = db.workspace();
+  workspace.namespaces.iter().flat_map(|n| &n.files).find(|f| f.id == file).unwrap().path.clone()
+}
+
+#[test]
+fn rename_texture_variable() {
+  let mut db = super::test_db();
+
+  let base = add_file(&mut db, 10, "models/block/base.json", FileType::Model);
+  db.set_file_text(
+    base,
+    r##"{
+  "textures": { "particle": "#side", "side": "test:blocks/bar" },
+  "elements": [{ "faces": { "north": { "texture": "#side" } } }]
+}"##
+      .into(),
+  );
+  let other = add_file(&mut db, 11, "models/block/other.json", FileType::Model);
+  db.set_file_text(other, r#"{ "parent": "test:block/base", "textures": { "side": "a" } }"#.into());
+  let unrelated = add_file(&mut db, 12, "models/block/unrelated.json", FileType::Model);
+  db.set_file_text(unrelated, r#"{ "textures": { "side": "a" } }"#.into());
+
+  rename(
+    db,
+    r#"{ "parent": "test:block/base", "textures": { "si|de": "b" } }"#,
+    "#wall",
+    expect![@r##"
+      test:models/block/foo.json:
+      { "parent": "test:block/base", "textures": { "wall": "b" } }
+      test:models/block/base.json:
+      {
+        "textures": { "particle": "#wall", "wall": "test:blocks/bar" },
+        "elements": [{ "faces": { "north": { "texture": "#wall" } } }]
+      }
+      test:models/block/other.json:
+      { "parent": "test:block/base", "textures": { "wall": "a" } }
+    "##],
+  );
+}
+
+#[test]
+fn rename_vanilla_texture_variable() {
+  let mut db = super::test_db();
+
+  db.set_file_text(
+    BLOCK_MODEL,
+    r##"{ "elements": [{ "faces": { "north": { "texture": "#side" } } }] }"##.into(),
+  );
+
+  rename(
+    db,
+    r#"{ "parent": "block/block", "textures": { "si|de": "b" } }"#,
+    "#wall",
+    expect![@r#"
+      error: `#side` is also used by `models/block/block.json`, which isn't part of the project
+    "#],
+  );
+}
+
+#[test]
+fn move_files() {
+  let mut db = super::test_db();
+
+  db.set_file_type(BAR_TEXTURE, FileType::Texture);
+  db.set_file_text(FOO_MODEL, r#"{ "textures": { "all": "test:blocks/bar" } }"#.into());
+  let child = add_file(&mut db, 10, "models/block/child.json", FileType::Model);
+  db.set_file_text(child, r#"{ "parent": "test:block/foo" }"#.into());
+  let blockstate = add_file(&mut db, 11, "blockstates/foo.json", FileType::Blockstate);
+  db.set_file_text(blockstate, r#"{ "variants": { "": { "model": "test:foo" } } }"#.into());
+
+  let analysis = AnalysisHost { db }.snapshot();
+  let move_file = |file, path: &str| {
+    let rename = analysis.rename_file(file, &path.parse().unwrap()).unwrap();
+    let mut actual = render_edits(&analysis.db, &rename.edits);
+    for error in rename.errors {
+      actual += &format!("error: {error}\n");
+    }
+    actual
+  };
+
+  expect![@r#"
+    test:models/block/child.json:
+    { "parent": "test:block/machine/foo" }
+    test:blockstates/foo.json:
+    { "variants": { "": { "model": "test:machine/foo" } } }
+  "#]
+  .assert_eq(&move_file(FOO_MODEL, "test:models/block/machine/foo.json"));
+
+  // Blockstates can only refer to models in `block` before 1.13.
+  expect![@r#"
+    test:models/block/child.json:
+    { "parent": "other:item/foo" }
+    error: `test:blockstates/foo.json` can only refer to models in `block/`, so it can't refer to `other:item/foo`
+  "#]
+  .assert_eq(&move_file(FOO_MODEL, "other:models/item/foo.json"));
+
+  expect![@r#"
+    test:models/block/foo.json:
+    { "textures": { "all": "test:block/bar" } }
+  "#]
+  .assert_eq(&move_file(BAR_TEXTURE, "test:textures/block/bar.png"));
+}
+
+#[test]
+fn rename_path() {
+  let mut db = super::test_db();
+
+  let child = add_file(&mut db, 10, "models/block/child.json", FileType::Model);
+  db.set_file_text(child, r#"{ "parent": "test:block/base" }"#.into());
+  let base = add_file(&mut db, 11, "models/block/base.json", FileType::Model);
+  db.set_file_text(base, "{}".into());
+
+  rename(
+    db,
+    r#"{ "parent": "test:block/ba|se" }"#,
+    "test:block/machine/base",
+    expect![@r#"
+      test:models/block/foo.json:
+      { "parent": "test:block/machine/base" }
+      test:models/block/child.json:
+      { "parent": "test:block/machine/base" }
+      moved test:models/block/base.json to test:models/block/machine/base.json
+    "#],
+  );
+}
+
+#[test]
+fn rename_path_out_of_block() {
+  let mut db = super::test_db();
+
+  let base = add_file(&mut db, 10, "models/block/base.json", FileType::Model);
+  db.set_file_text(base, "{}".into());
+  let blockstate = add_file(&mut db, 11, "blockstates/base.json", FileType::Blockstate);
+  db.set_file_text(blockstate, r#"{ "variants": { "": { "model": "test:base" } } }"#.into());
+
+  rename(
+    db,
+    r#"{ "parent": "test:block/ba|se" }"#,
+    "test:item/base",
+    expect![@r#"
+    error: `test:blockstates/base.json` can only refer to models in `block/`, so it can't refer to `test:item/base`
+  "#],
+  );
+}
@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use mc_hir::HirDatabase;
+use mc_source::{File, FileId, FileType, Layer, Namespace, SourceDatabase, Workspace};
+use mc_test::{expect, Expect};
+
+use super::{add_file, FOO_MODEL};
+use crate::{database::RootDatabase, symbol::DocumentSymbol};
+
+/// Prints the outline of the given file, one symbol per line.
+fn symbols(db: &mut RootDatabase, file: FileId, input: &str, expect: Expect) {
+  db.set_file_text(file, input.into());
+
+  fn render(out: &mut String, symbols: &[DocumentSymbol], depth: usize) {
+    for symbol in symbols {
+      *out += &format!("{}{:?} {}", "  ".repeat(depth), symbol.kind, symbol.name);
+      if let Some(ref detail) = symbol.detail {
+        *out += &format!(" ({detail})");
+      }
+      *out += "\n";
+      render(out, &symbol.children, depth + 1);
+    }
+  }
+
+  let mut actual = String::new();
+  render(&mut actual, &crate::symbol::document_symbols(db, file), 0);
+  expect.assert_eq(&actual);
+}
+
+#[test]
+fn model_symbols() {
+  symbols(
+    &mut super::test_db(),
+    FOO_MODEL,
+    r##"{
+      "parent": "block/block",
+      "textures": { "side": "test:blocks/bar", "particle": "#side" },
+      "elements": [
+        {
+          "from": [0, 0, 0],
+          "to": [16, 8, 16],
+          "faces": { "north": { "texture": "#side" }, "up": { "texture": "#top" } }
+        },
+        { "name": "pole", "from": [7, 8, 7], "to": [9, 16, 9] }
+      ]
+    }"##,
+    expect![@r#"
+      Model block/block (parent)
+      Texture #side (test:blocks/bar)
+      Texture #particle (#side)
+      Element [0, 0, 0] → [16, 8, 16]
+        Face north (#side)
+        Face up (#top)
+      Element pole ([7, 8, 7] → [9, 16, 9])
+    "#],
+  );
+}
+
+#[test]
+fn blockstate_symbols() {
+  let mut db = super::test_db();
+  let variants = add_file(&mut db, 10, "blockstates/foo.json", FileType::Blockstate);
+  let multipart = add_file(&mut db, 11, "blockstates/bar.json", FileType::Blockstate);
+
+  symbols(
+    &mut db,
+    variants,
+    r#"{
+      "variants": {
+        "facing=north": { "model": "block/foo" },
+        "facing=south": [{ "model": "block/foo" }, { "model": "block/bar" }]
+      }
+    }"#,
+    expect![@r#"
+      Variant facing=north
+        Model block/foo
+      Variant facing=south
+        Model block/foo
+        Model block/bar
+    "#],
+  );
+
+  symbols(
+    &mut db,
+    multipart,
+    r#"{
+      "multipart": [
+        { "apply": { "model": "block/post" } },
+        { "when": { "north": "true", "up": "false|side" }, "apply": { "model": "block/side" } },
+        { "when": { "OR": [{ "east": "true" }, { "west": "!none" }] }, "apply": { "model": "block/x" } }
+      ]
+    }"#,
+    expect![@r#"
+      Multipart always
+        Model block/post
+      Multipart when north=true,up=false|side
+        Model block/side
+      Multipart when east=true || west=!none
+        Model block/x
+    "#],
+  );
+}
+
+#[test]
+fn forge_symbols() {
+  let mut db = super::test_db();
+  let file = add_file(&mut db, 10, "blockstates/foo.json", FileType::Blockstate);
+
+  symbols(
+    &mut db,
+    file,
+    r#"{
+      "forge_marker": 1,
+      "defaults": { "model": "foo", "textures": { "all": "test:blocks/bar" } },
+      "variants": {
+        "facing": {
+          "north": { "y": 0 },
+          "south": { "model": "bar" }
+        },
+        "inventory": [{ "model": "item" }]
+      }
+    }"#,
+    expect![@r#"
+      Variant defaults
+        Model foo
+        Texture #all (test:blocks/bar)
+      Property facing
+        Variant north
+        Variant south
+          Model bar
+      Variant inventory
+        Model item
+    "#],
+  );
+}
+
+/// Prints the results of a workspace symbol search, best match first.
+fn search(db: &RootDatabase, query: &str, expect: Expect) {
+  let index = db.symbol_index();
+
+  let mut actual = String::new();
+  for symbol in index.search(query, 10) {
+    actual += &format!("{:?} {}\n", symbol.ty, symbol.name);
+  }
+  expect.assert_eq(&actual);
+}
+
+#[test]
+fn workspace_symbols() {
+  let mut db = super::test_db();
+  add_file(&mut db, 10, "models/block/machine_frame.json", FileType::Model);
+  add_file(&mut db, 11, "blockstates/machine_frame.json", FileType::Blockstate);
+  add_file(&mut db, 12, "textures/blocks/machine_frame.png", FileType::Texture);
+  add_file(&mut db, 13, "textures/blocks/machine_frame.png.mcmeta", FileType::TextureMeta);
+  add_file(&mut db, 14, "models/block/campfire.json", FileType::Model);
+
+  search(
+    &db,
+    "mafr",
+    expect![@r#"
+    Blockstate test:machine_frame
+    Model test:block/machine_frame
+    Texture test:blocks/machine_frame
+    TextureMeta test:blocks/machine_frame
+  "#],
+  );
+  search(
+    &db,
+    "test:mf",
+    expect![@r#"
+    Blockstate test:machine_frame
+    Model test:block/machine_frame
+    Texture test:blocks/machine_frame
+    TextureMeta test:blocks/machine_frame
+    Model test:block/campfire
+  "#],
+  );
+  search(
+    &db,
+    "block",
+    expect![@r#"
+    Model test:block/foo
+    Texture test:blocks/bar
+    Model test:block/campfire
+    Model minecraft:block/block
+    Model test:block/machine_frame
+    Texture test:blocks/machine_frame
+    TextureMeta test:blocks/machine_frame
+  "#],
+  );
+  search(&db, "zzz", expect![@r#""#]);
+}
+
+#[test]
+fn workspace_symbols_skip_overridden() {
+  let mut db = RootDatabase::default();
+
+  let namespace = |layer, files: &[(u32, &str, FileType)]| Namespace {
+    name: "minecraft".to_string(),
+    layer,
+    files: files
+      .iter()
+      .map(|&(id, path, ty)| File { id: FileId::new_raw(id), ty, path: path.parse().unwrap() })
+      .collect(),
+  };
+  db.set_workspace(Arc::new(Workspace {
+    namespaces:        vec![
+      namespace(
+        Layer::Vanilla,
+        &[
+          (0, "models/block/stone.json", FileType::Model),
+          (1, "models/block/dirt.json", FileType::Model),
+          (2, "textures/block/stone.png", FileType::Texture),
+        ],
+      ),
+      namespace(Layer::Project, &[(3, "models/block/stone.json", FileType::Model)]),
+      namespace(
+        Layer::Dependency,
+        &[
+          (4, "models/block/stone.json", FileType::Model),
+          (5, "textures/block/stone.png", FileType::Texture),
+        ],
+      ),
+    ],
+    packs:             vec![],
+    minecraft_version: None,
+  }));
+
+  let index = db.symbol_index();
+  let mut actual = String::new();
+  for symbol in index.search("", 10) {
+    actual += &format!("{:?} {} ({:?})\n", symbol.ty, symbol.name, symbol.file);
+  }
+  expect![@r#"
+    Model minecraft:block/dirt (FileId(1))
+    Model minecraft:block/stone (FileId(3))
+    Texture minecraft:block/stone (FileId(5))
+  "#].assert_eq(&actual);
+}
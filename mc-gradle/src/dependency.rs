@@ -0,0 +1,234 @@
+//! Finds the mod dependencies of a gradle project, and their jars in the
+//! gradle cache.
+
+use std::{
+  collections::HashMap,
+  io,
+  path::{Path, PathBuf},
+};
+
+/// Dependency configurations that may pull in mods with assets.
+const CONFIGURATIONS: &[&str] = &[
+  "implementation",
+  "api",
+  "compileOnly",
+  "runtimeOnly",
+  "compile",
+  "provided",
+  "deobfCompile",
+  "deobfProvided",
+  "modImplementation",
+  "modApi",
+  "modCompileOnly",
+  "modRuntimeOnly",
+  "localRuntime",
+];
+
+/// A maven coordinate, like `mezz.jei:jei_1.12.2:4.16.1.302`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Dependency {
+  pub group:      String,
+  pub name:       String,
+  pub version:    String,
+  pub classifier: Option<String>,
+}
+
+impl Dependency {
+  pub fn parse(s: &str) -> Option<Dependency> {
+    let mut parts = s.split(':');
+    let group = parts.next()?;
+    let name = parts.next()?;
+    let version = parts.next()?;
+    let classifier = parts.next();
+    if parts.next().is_some() {
+      return None;
+    }
+
+    let valid = |s: &str| {
+      !s.is_empty()
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+    };
+    if !valid(group) || !valid(name) || !valid(version) || !classifier.is_none_or(valid) {
+      return None;
+    }
+
+    Some(Dependency {
+      group:      group.to_string(),
+      name:       name.to_string(),
+      version:    version.to_string(),
+      classifier: classifier.map(|s| s.to_string()),
+    })
+  }
+
+  fn jar_name(&self) -> String {
+    match self.classifier {
+      Some(ref classifier) => format!("{}-{}-{}.jar", self.name, self.version, classifier),
+      None => format!("{}-{}.jar", self.name, self.version),
+    }
+  }
+}
+
+/// Finds all the dependencies declared in the build scripts of the root project
+/// and its direct subprojects. `${property}` references are resolved from
+/// `gradle.properties`.
+pub fn detect_dependencies(root: &Path) -> Vec<Dependency> {
+  let properties =
+    std::fs::read_to_string(root.join("gradle.properties")).map(|s| parse_properties(&s));
+  let properties = properties.unwrap_or_default();
+
+  let mut projects = vec![root.to_path_buf()];
+  if let Ok(dir) = std::fs::read_dir(root) {
+    let mut subprojects = dir.filter_map(|e| e.ok()).map(|e| e.path()).collect::<Vec<_>>();
+    subprojects.sort();
+    projects.extend(subprojects.into_iter().filter(|p| p.is_dir()));
+  }
+
+  let mut dependencies = vec![];
+  for project in projects {
+    for name in ["build.gradle", "build.gradle.kts"] {
+      let Ok(text) = std::fs::read_to_string(project.join(name)) else { continue };
+
+      for dep in dependencies_from_build_script(&text, &properties) {
+        if !dependencies.contains(&dep) {
+          dependencies.push(dep);
+        }
+      }
+    }
+  }
+
+  dependencies
+}
+
+/// Finds the jar for the given dependency in the gradle module cache.
+pub fn find_dependency_jar(dep: &Dependency) -> io::Result<PathBuf> {
+  let dir = crate::gradle_home()?
+    .join("caches")
+    .join("modules-2")
+    .join("files-2.1")
+    .join(&dep.group)
+    .join(&dep.name)
+    .join(&dep.version);
+
+  // Each file is stored in a directory named after its hash.
+  let jar_name = dep.jar_name();
+  for entry in std::fs::read_dir(&dir)? {
+    let path = entry?.path().join(&jar_name);
+    if path.is_file() {
+      return Ok(path);
+    }
+  }
+
+  Err(io::Error::new(
+    io::ErrorKind::NotFound,
+    format!("could not find {jar_name} in {}", dir.display()),
+  ))
+}
+
+fn parse_properties(text: &str) -> HashMap<String, String> {
+  text
+    .lines()
+    .filter(|line| !line.trim_start().starts_with('#'))
+    .filter_map(|line| {
+      let (key, value) = line.split_once('=')?;
+      Some((key.trim().to_string(), value.trim().to_string()))
+    })
+    .collect()
+}
+
+fn dependencies_from_build_script(
+  text: &str,
+  properties: &HashMap<String, String>,
+) -> Vec<Dependency> {
+  text
+    .lines()
+    .filter_map(|line| {
+      let line = line.trim();
+      let configuration = line.split(|c: char| !c.is_ascii_alphanumeric()).next()?;
+      if !CONFIGURATIONS.contains(&configuration) {
+        return None;
+      }
+
+      // The coordinate is the first quoted string, which may be wrapped in
+      // something like `fg.deobf(...)`.
+      let start = line.find(['"', '\''])?;
+      let quote = line[start..].chars().next()?;
+      let rest = &line[start + 1..];
+      let end = rest.find(quote)?;
+
+      Dependency::parse(&substitute(&rest[..end], properties))
+    })
+    .collect()
+}
+
+/// Replaces `${key}` and `$key` with values from `gradle.properties`.
+fn substitute(s: &str, properties: &HashMap<String, String>) -> String {
+  let mut out = String::new();
+  let mut rest = s;
+
+  while let Some(i) = rest.find('$') {
+    out.push_str(&rest[..i]);
+    rest = &rest[i + 1..];
+
+    let (key, len) = match rest.strip_prefix('{') {
+      Some(inner) => match inner.find('}') {
+        Some(end) => (&inner[..end], end + 2),
+        None => break,
+      },
+      None => {
+        let is_key = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let end = rest.find(|c: char| !is_key(c)).unwrap_or(rest.len());
+        (&rest[..end], end)
+      }
+    };
+
+    // `project.foo` and `rootProject.foo` are also just properties.
+    let name = key.rsplit('.').next().unwrap_or(key);
+    match properties.get(name) {
+      Some(value) => out.push_str(value),
+      None => {
+        out.push('$');
+        out.push_str(&rest[..len]);
+      }
+    }
+    rest = &rest[len..];
+  }
+
+  out.push_str(rest);
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_dependencies() {
+    let properties = parse_properties("# comment\njei_version=4.16.1.302\n");
+
+    let deps = dependencies_from_build_script(
+      r#"
+      dependencies {
+        minecraft "net.minecraftforge:forge:1.12.2-14.23.5.2860"
+        implementation fg.deobf("mezz.jei:jei_1.12.2:${jei_version}")
+        compileOnly 'team.chisel.ctm:CTM:MC1.12.2-1.0.2.31:api'
+        implementation project(":core")
+        modImplementation("net.fabricmc.fabric-api:fabric-api:$fabric_version")
+      }
+      "#,
+      &properties,
+    );
+
+    assert_eq!(
+      deps,
+      vec![
+        Dependency::parse("mezz.jei:jei_1.12.2:4.16.1.302").unwrap(),
+        Dependency {
+          group:      "team.chisel.ctm".into(),
+          name:       "CTM".into(),
+          version:    "MC1.12.2-1.0.2.31".into(),
+          classifier: Some("api".into()),
+        },
+      ]
+    );
+  }
+}
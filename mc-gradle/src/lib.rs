@@ -4,66 +4,50 @@ use std::{
   path::PathBuf,
 };
 
-pub fn extract_jar() -> io::Result<PathBuf> {
-  let home = env::var("HOME").map_err(|_| io::Error::new(ErrorKind::Other, "HOME not set"))?;
+mod dependency;
+mod version;
 
-  // NB: This is just the client jar. We could pull this down from mojang, but we
-  // can also just assume that forge gradle has pulled it down, and unzip it
-  // locally.
-  //
-  // TODO: Needs way, way more error handling.
-  let mut path = PathBuf::new();
-  path.push(&home);
-  path.extend(&[
-    ".gradle",
-    "caches",
-    "forge_gradle",
-    "minecraft_repo",
-    "versions",
-    "1.12.2",
-    "client.jar",
-  ]);
+pub use dependency::{detect_dependencies, find_dependency_jar, Dependency};
+pub use version::detect_version;
 
-  let mut destination = PathBuf::new();
-  destination.push(&home);
-  destination.extend(&[".cache", "mclsp", "minecraft", "versions", "1.12.2", "client"]);
+/// Returns the gradle user home, which is `$GRADLE_USER_HOME` or
+/// `~/.gradle`.
+pub fn gradle_home() -> io::Result<PathBuf> {
+  if let Ok(home) = env::var("GRADLE_USER_HOME") {
+    return Ok(PathBuf::from(home));
+  }
 
-  std::fs::create_dir_all(&destination).unwrap();
+  Ok(home()?.join(".gradle"))
+}
 
-  let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).map_err(|e| {
-    io::Error::new(
-      ErrorKind::InvalidData,
-      format!("failed to open vanilla jar at {}: {}", path.display(), e),
-    )
-  })?)
-  .map_err(|e| {
+fn home() -> io::Result<PathBuf> {
+  env::var("HOME").map(PathBuf::from).map_err(|_| io::Error::new(ErrorKind::Other, "HOME not set"))
+}
+
+/// Finds the vanilla client jar for the given version in the gradle cache.
+///
+/// NB: We could pull this down from mojang, but we can also just assume that
+/// the build tool has pulled it down already.
+pub fn find_client_jar(version: &str) -> io::Result<PathBuf> {
+  let caches = gradle_home()?.join("caches");
+
+  let candidates = [
+    // ForgeGradle 3+.
+    caches.join(format!("forge_gradle/minecraft_repo/versions/{version}/client.jar")),
+    // ForgeGradle 2.
+    caches.join(format!("minecraft/net/minecraft/minecraft/{version}/minecraft-{version}.jar")),
+    // NeoGradle and ModDevGradle.
+    caches.join(format!("neoformruntime/artifacts/minecraft_{version}_client.jar")),
+    caches.join(format!("neoform/minecraft/{version}/client.jar")),
+    // Fabric Loom.
+    caches.join(format!("fabric-loom/{version}/minecraft-client.jar")),
+    caches.join(format!("fabric-loom/minecraft-{version}-client.jar")),
+  ];
+
+  candidates.iter().find(|p| p.is_file()).cloned().ok_or_else(|| {
     io::Error::new(
-      ErrorKind::InvalidData,
-      format!("failed to open vanilla jar at {}: {}", path.display(), e),
+      ErrorKind::NotFound,
+      format!("could not find the client jar for minecraft {version} in {}", caches.display()),
     )
-  })?;
-
-  for i in 0..archive.len() {
-    let name = archive.name_for_index(i).unwrap();
-    let mut path = destination.clone();
-    path.push(name);
-
-    // We only care about models and textures.
-    if !name.ends_with(".json") && !name.ends_with(".png") {
-      continue;
-    }
-
-    if let Some(parent) = path.parent() {
-      std::fs::create_dir_all(&parent)?;
-    }
-
-    let mut input = archive.by_index(i).unwrap();
-    let mut out = std::fs::File::create(&path)?;
-    std::io::copy(&mut input, &mut out)?;
-  }
-
-  Ok(destination)
+  })
 }
-
-#[test]
-fn test_extract_jar() { extract_jar().unwrap(); }
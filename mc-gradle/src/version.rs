@@ -0,0 +1,121 @@
+//! Detects the minecraft version of a gradle project.
+
+use std::path::Path;
+
+/// Keys in `gradle.properties` that are commonly used for the minecraft
+/// version.
+const PROPERTY_KEYS: &[&str] = &["minecraft_version", "mc_version", "minecraftVersion"];
+
+/// Detects the minecraft version from `gradle.properties`, or failing that,
+/// from the dependencies in `build.gradle`.
+pub fn detect_version(root: &Path) -> Option<String> {
+  if let Ok(text) = std::fs::read_to_string(root.join("gradle.properties")) {
+    if let Some(version) = version_from_properties(&text) {
+      return Some(version);
+    }
+  }
+
+  for name in ["build.gradle", "build.gradle.kts"] {
+    if let Ok(text) = std::fs::read_to_string(root.join(name)) {
+      if let Some(version) = version_from_build_script(&text) {
+        return Some(version);
+      }
+    }
+  }
+
+  None
+}
+
+fn version_from_properties(text: &str) -> Option<String> {
+  text.lines().find_map(|line| {
+    let (key, value) = line.split_once('=')?;
+    if PROPERTY_KEYS.contains(&key.trim()) {
+      Some(value.trim().to_string())
+    } else {
+      None
+    }
+  })
+}
+
+fn version_from_build_script(text: &str) -> Option<String> {
+  text.lines().find_map(|line| {
+    let line = line.trim();
+
+    // Fabric loom: `minecraft "com.mojang:minecraft:1.20.1"`.
+    if let Some(rest) = after(line, "com.mojang:minecraft:") {
+      return Some(rest.to_string());
+    }
+
+    // ForgeGradle 3+: `minecraft 'net.minecraftforge:forge:1.16.5-36.2.39'`.
+    if let Some(rest) = after(line, "net.minecraftforge:forge:") {
+      return rest.split('-').next().map(|s| s.to_string());
+    }
+
+    // ForgeGradle 2: `version = "1.12.2-14.23.5.2860"`, within a `minecraft`
+    // block. Other `version`s are rarely in this form, so its good enough.
+    if let Some(rest) = line.strip_prefix("version") {
+      let rest = rest.trim_start().strip_prefix('=')?;
+      let value = rest.trim().trim_matches(['"', '\'']);
+      let (mc, forge) = value.split_once('-')?;
+      if is_version(mc) && is_version(forge) {
+        return Some(mc.to_string());
+      }
+    }
+
+    None
+  })
+}
+
+/// Returns the text after `prefix`, up to the next quote.
+fn after<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+  let start = line.find(prefix)? + prefix.len();
+  let rest = &line[start..];
+  let end = rest.find(['"', '\'']).unwrap_or(rest.len());
+  let version = &rest[..end];
+
+  if version.is_empty() || version.starts_with('$') {
+    None
+  } else {
+    Some(version)
+  }
+}
+
+fn is_version(s: &str) -> bool {
+  !s.is_empty()
+    && s.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detects_properties() {
+    assert_eq!(
+      version_from_properties("org.gradle.jvmargs=-Xmx3G\nminecraft_version = 1.20.1\n"),
+      Some("1.20.1".into())
+    );
+    assert_eq!(version_from_properties("mod_version=1.2.3\n"), None);
+  }
+
+  #[test]
+  fn detects_build_script() {
+    assert_eq!(
+      version_from_build_script("  minecraft \"com.mojang:minecraft:1.21\"\n"),
+      Some("1.21".into())
+    );
+    assert_eq!(
+      version_from_build_script("  minecraft 'net.minecraftforge:forge:1.16.5-36.2.39'\n"),
+      Some("1.16.5".into())
+    );
+    assert_eq!(
+      version_from_build_script("minecraft {\n  version = \"1.12.2-14.23.5.2860\"\n}\n"),
+      Some("1.12.2".into())
+    );
+    assert_eq!(version_from_build_script("version = \"1.0.0\"\n"), None);
+    assert_eq!(
+      version_from_build_script("minecraft \"com.mojang:minecraft:${minecraft_version}\"\n"),
+      None
+    );
+  }
+}
@@ -3,10 +3,11 @@ use std::{collections::HashMap, sync::Arc};
 mod parse;
 mod validate;
 
-pub use validate::PropIter;
+pub(crate) use validate::{check_prop_key, check_prop_value};
+pub use validate::{condition_values, PropIter};
 
 use la_arena::{Arena, Idx};
-use mc_source::{FileId, Path};
+use mc_source::{FileId, ModelPath, Path, Version};
 use mc_syntax::{ast, AstPtr};
 
 use crate::{diagnostic::Diagnostics, model::F64Eq, HirDatabase};
@@ -16,30 +17,82 @@ pub struct Blockstate {
   pub nodes: Arena<Node>,
 }
 
+impl Blockstate {
+  pub fn is_multipart(&self) -> bool {
+    self.nodes.values().any(|n| matches!(n, Node::Multipart(_)))
+  }
+}
+
 pub type NodeId = Idx<Node>;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Node {
   Variant(Variant),
+  Multipart(Multipart),
+  Choice(Choice),
+  Prop(PropCondition),
   Model(Model),
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct BlockstateSourceMap {
-  pub ast_variants: HashMap<AstPtr<ast::Element>, NodeId>,
-  pub ast_models:   HashMap<AstPtr<ast::Value>, NodeId>,
-
-  pub variants: HashMap<NodeId, AstPtr<ast::Element>>,
-  pub models:   HashMap<NodeId, AstPtr<ast::Value>>,
+  pub ast_variants:   HashMap<AstPtr<ast::Element>, NodeId>,
+  pub ast_multiparts: HashMap<AstPtr<ast::Value>, NodeId>,
+  pub ast_choices:    HashMap<AstPtr<ast::Value>, NodeId>,
+  pub ast_props:      HashMap<AstPtr<ast::Element>, NodeId>,
+  pub ast_models:     HashMap<AstPtr<ast::Value>, NodeId>,
+
+  pub variants:   HashMap<NodeId, AstPtr<ast::Element>>,
+  pub multiparts: HashMap<NodeId, AstPtr<ast::Value>>,
+  pub choices:    HashMap<NodeId, AstPtr<ast::Value>>,
+  pub props:      HashMap<NodeId, AstPtr<ast::Element>>,
+  pub models:     HashMap<NodeId, AstPtr<ast::Value>>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Variant {
-  pub name:   String,
+  pub name:    String,
+  /// The `Node::Choice`s for this variant. If there are multiple, one is picked
+  /// at random based on its weight.
+  pub choices: Vec<NodeId>,
+}
+
+/// A single case of a `multipart` blockstate. The model is applied if `when`
+/// matches, or always if there is no `when`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Multipart {
+  pub when:  Option<Condition>,
+  /// The `Node::Choice`s to pick from, like `Variant::choices`.
+  pub apply: Vec<NodeId>,
+}
+
+/// A model that a variant or multipart case may use.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Choice {
   pub model:  NodeId,
   pub x:      Option<F64Eq>,
   pub y:      Option<F64Eq>,
   pub uvlock: Option<bool>,
+  pub weight: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Condition {
+  Or(Vec<Condition>),
+  And(Vec<Condition>),
+
+  /// Matches if all of these `Node::Prop`s match.
+  Props(Vec<NodeId>),
+}
+
+/// A single property in a `when` condition, like `"north": "true|side"`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PropCondition {
+  pub key:     String,
+  /// The `|` separated values. The condition matches any of these.
+  pub values:  Vec<String>,
+  /// Set if the value starts with `!`, which inverts the match.
+  pub negated: bool,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -47,6 +100,22 @@ pub struct Model {
   pub path: Path,
 }
 
+/// Returns the model that a model path in a blockstate refers to. Before 1.13,
+/// these are relative to `models/block`.
+pub fn model_path(db: &dyn HirDatabase, path: &Path) -> ModelPath {
+  let mut path = path.clone();
+  if let Some(prefix) = model_prefix(db) {
+    path.segments.insert(0, prefix.into());
+  }
+  ModelPath { path }
+}
+
+/// The implicit directory that model paths are in, if any.
+pub fn model_prefix(db: &dyn HirDatabase) -> Option<&'static str> {
+  let flattened = db.minecraft_version().is_some_and(|v| v >= Version::FLATTENING);
+  (!flattened).then_some("block")
+}
+
 pub fn parse_blockstate(
   db: &dyn HirDatabase,
   file_id: FileId,
@@ -1,4 +1,7 @@
-use crate::{diagnostic::Diagnostics, parse::Parser};
+use crate::{
+  diagnostic::{first_char, Diagnostics},
+  parse::Parser,
+};
 use la_arena::RawIdx;
 use mc_syntax::{
   ast::{self, AstNode},
@@ -36,8 +39,15 @@ impl BlockstateParser<'_> {
             let Some(key) = element.key().map(|k| k.parse_text()) else { continue };
             let Some(value) = element.value() else { continue };
 
-            if let Some(variant) = self.parse_variant(key, value) {
-              self.alloc(element, variant);
+            let variant = self.parse_variant(key, value);
+            self.alloc(element, variant);
+          }
+        }
+        "multipart" => {
+          let Some(cases) = self.parser.array(value) else { continue };
+          for case in cases.values() {
+            if let Some(multipart) = self.parse_multipart(case.clone()) {
+              self.alloc(case, multipart);
             }
           }
         }
@@ -46,27 +56,155 @@ impl BlockstateParser<'_> {
     }
   }
 
-  fn parse_variant(&mut self, key: String, e: ast::Value) -> Option<Variant> {
-    let mut variant = Variant {
-      name:   key,
+  fn parse_variant(&mut self, key: String, v: ast::Value) -> Variant {
+    Variant { name: key, choices: self.parse_choices(v) }
+  }
+
+  /// Parses either a single model, or an array of weighted models.
+  fn parse_choices(&mut self, v: ast::Value) -> Vec<NodeId> {
+    let ast::Value::Array(ref arr) = v else {
+      let Some(choice) = self.parse_choice(v.clone(), false) else { return vec![] };
+      return vec![self.alloc(v, choice)];
+    };
+
+    if arr.values().next().is_none() {
+      self.parser.diagnostics.error(v.syntax(), "expected at least one model");
+    }
+
+    let mut choices = vec![];
+    for value in arr.values() {
+      if let Some(choice) = self.parse_choice(value.clone(), true) {
+        choices.push(self.alloc(value, choice));
+      }
+    }
+    choices
+  }
+
+  fn parse_choice(&mut self, c: ast::Value, in_array: bool) -> Option<Choice> {
+    let mut model = None;
+    let mut choice = Choice {
       model:  NodeId::from_raw(RawIdx::from_u32(0)),
       x:      None,
       y:      None,
       uvlock: None,
+      weight: None,
     };
 
-    let obj = self.parser.object(e)?;
+    let obj = self.parser.object(c)?;
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "model" => model = Some(self.parse_path(value)),
+        "x" => choice.x = self.parser.float(&value).map(F64Eq),
+        "y" => choice.y = self.parser.float(&value).map(F64Eq),
+        "uvlock" => choice.uvlock = self.parser.bool(&value),
+        "weight" => {
+          if !in_array {
+            self
+              .parser
+              .diagnostics
+              .warn(key.syntax(), "`weight` is only used when there are multiple models")
+              .hint("put this model in an array with the other models to pick from");
+          }
+          choice.weight = self.weight(&value);
+        }
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    match model {
+      Some(model) => choice.model = model?,
+      None => {
+        self.parser.diagnostics.error(first_char(obj.syntax().text_range()), "missing `model`");
+        return None;
+      }
+    }
+
+    Some(choice)
+  }
+
+  fn weight(&mut self, value: &ast::Value) -> Option<u32> {
+    let n = self.parser.int(value)?;
+    match u32::try_from(n) {
+      Ok(n) if n > 0 => Some(n),
+      _ => {
+        self.parser.diagnostics.error(value.syntax(), "weight must be a positive integer");
+        None
+      }
+    }
+  }
+
+  fn parse_multipart(&mut self, m: ast::Value) -> Option<Multipart> {
+    let mut when = None;
+    let mut apply = None;
+
+    let obj = self.parser.object(m)?;
     for (key, value) in obj.iter() {
       match key.parse_text().as_str() {
-        "model" => variant.model = self.parse_path(value)?,
-        "x" => variant.x = Some(F64Eq(self.parser.float(&value)?)),
-        "y" => variant.y = Some(F64Eq(self.parser.float(&value)?)),
-        "uvlock" => variant.uvlock = Some(self.parser.bool(&value)?),
+        "when" => when = self.parse_condition(value),
+        "apply" => apply = Some(self.parse_choices(value)),
         _ => self.parser.warn_unknown_key(key),
       }
     }
 
-    Some(variant)
+    if apply.is_none() {
+      self.parser.diagnostics.error(first_char(obj.syntax().text_range()), "missing `apply`");
+    }
+
+    Some(Multipart { when, apply: apply? })
+  }
+
+  fn parse_condition(&mut self, c: ast::Value) -> Option<Condition> {
+    let obj = self.parser.object(c)?;
+    let len = obj.elements().count();
+    if len == 0 {
+      self.parser.diagnostics.error(obj.syntax(), "empty condition");
+      return None;
+    }
+
+    for (key, value) in obj.iter() {
+      let op = key.parse_text();
+      if op != "OR" && op != "AND" {
+        continue;
+      }
+
+      if len != 1 {
+        self
+          .parser
+          .diagnostics
+          .error(key.syntax(), format!("`{op}` must be the only key in a condition"));
+        return None;
+      }
+
+      let arr = self.parser.array(value)?;
+      let conditions = arr.values().filter_map(|v| self.parse_condition(v)).collect();
+      return Some(if op == "OR" { Condition::Or(conditions) } else { Condition::And(conditions) });
+    }
+
+    let mut props = vec![];
+    for element in obj.elements() {
+      let Some(key) = element.key().map(|k| k.parse_text()) else { continue };
+      let Some(value) = element.value() else { continue };
+
+      let text = match value {
+        ast::Value::StringValue(_) => value.as_str().unwrap(),
+        // Vanilla converts any primitive to a string, so `true` and `3` work here.
+        ast::Value::Boolean(_) | ast::Value::NumberValue(_) => value.syntax().text().to_string(),
+        _ => {
+          self.parser.diagnostics.error(value.syntax(), "expected string");
+          continue;
+        }
+      };
+
+      let (negated, text) = match text.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, text.as_str()),
+      };
+      let values = text.split('|').map(|v| v.to_string()).collect();
+
+      props.push(self.alloc(element, PropCondition { key, values, negated }));
+    }
+
+    Some(Condition::Props(props))
   }
 
   fn parse_path(&mut self, p: ast::Value) -> Option<NodeId> {
@@ -99,6 +237,39 @@ impl BlockstateNode for Variant {
   }
 }
 
+impl BlockstateNode for Multipart {
+  type Ast = ast::Value;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut BlockstateParser) -> NodeId {
+    let id = parser.blockstate.nodes.alloc(Node::Multipart(self));
+    parser.source_map.multiparts.insert(id, AstPtr::new(elem));
+    parser.source_map.ast_multiparts.insert(AstPtr::new(elem), id);
+    id
+  }
+}
+
+impl BlockstateNode for Choice {
+  type Ast = ast::Value;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut BlockstateParser) -> NodeId {
+    let id = parser.blockstate.nodes.alloc(Node::Choice(self));
+    parser.source_map.choices.insert(id, AstPtr::new(elem));
+    parser.source_map.ast_choices.insert(AstPtr::new(elem), id);
+    id
+  }
+}
+
+impl BlockstateNode for PropCondition {
+  type Ast = ast::Element;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut BlockstateParser) -> NodeId {
+    let id = parser.blockstate.nodes.alloc(Node::Prop(self));
+    parser.source_map.props.insert(id, AstPtr::new(elem));
+    parser.source_map.ast_props.insert(AstPtr::new(elem), id);
+    id
+  }
+}
+
 impl BlockstateNode for Model {
   type Ast = ast::Value;
 
@@ -1,18 +1,25 @@
 use std::collections::{HashMap, HashSet};
 
-use mc_source::{FileId, TextRange, TextSize};
+use mc_source::{FileId, TextRange, TextSize, Version};
 use mc_syntax::{
   ast::{self, AstNode},
   Json, Parse, SyntaxNode,
 };
 
-use crate::{diagnostic::Diagnostics, HirDatabase};
+use crate::{
+  diagnostic::{first_char, Diagnostics},
+  suggest, HirDatabase,
+};
 
-use super::{Blockstate, BlockstateSourceMap, Node};
+use super::{Blockstate, BlockstateSourceMap, Node, PropCondition};
 
 struct Validator<'a> {
+  db:         &'a dyn HirDatabase,
   blockstate: &'a Blockstate,
 
+  /// After 1.13, blocks without properties use `""` instead of `normal`.
+  flattened: bool,
+
   source_map:  &'a BlockstateSourceMap,
   json:        &'a Parse<Json>,
   diagnostics: &'a mut Diagnostics,
@@ -26,7 +33,9 @@ pub fn validate(
   diagnostics: &mut Diagnostics,
 ) {
   let blockstate = db.parse_blockstate(file_id);
-  let mut validator = Validator { blockstate: &blockstate, source_map, json, diagnostics };
+  let flattened = db.minecraft_version().is_some_and(|v| v >= Version::FLATTENING);
+  let mut validator =
+    Validator { db, blockstate: &blockstate, flattened, source_map, json, diagnostics };
   validator.validate_blockstate();
 }
 
@@ -47,19 +56,38 @@ impl Validator<'_> {
           all_defined.insert(variant.name.clone(), syntax.text_range());
           self.check_prop_list(&variant.name, syntax);
         }
+        Node::Prop(prop) => {
+          let element = ast::Element::cast(self.source_map.props[&id].to_node(self.json)).unwrap();
+          self.check_condition(prop, &element);
+        }
+        Node::Model(model)
+          if self.db.lookup_model(super::model_path(self.db, &model.path)).is_none() =>
+        {
+          let value = self.source_map.models[&id].tree(self.json);
+          let prefix = super::model_prefix(self.db);
+          suggest::missing_model(
+            self.db,
+            self.diagnostics,
+            &value,
+            &model.path.to_string(),
+            prefix.as_slice(),
+          );
+        }
         _ => {}
       }
     }
 
+    // Multipart blockstates don't need any variants.
+    if all_defined.is_empty() && self.blockstate.is_multipart() {
+      return;
+    }
+
     let outer_span = match self.json.tree().value().unwrap() {
       ast::Value::Object(obj) => {
         let mut range = None;
         for (key, value) in obj.iter() {
           if key.parse_text().as_str() == "variants" {
-            // Only underline the first character, as underlining everything is too
-            // annoying.
-            let start = value.syntax().text_range().start();
-            range = Some(TextRange::new(start, start + TextSize::from(1)));
+            range = Some(first_char(value.syntax().text_range()));
             break;
           }
         }
@@ -72,7 +100,11 @@ impl Validator<'_> {
     };
 
     if all_defined.is_empty() {
-      self.diagnostics.error(outer_span, "missing 'normal' variant");
+      if self.flattened {
+        self.diagnostics.error(outer_span, "missing \"\" variant");
+      } else {
+        self.diagnostics.error(outer_span, "missing 'normal' variant");
+      }
     } else if all_defined.len() > 1 {
       // We only want to check multivariant if there are multiple properties
       // defined.
@@ -144,14 +176,22 @@ impl Validator<'_> {
   fn check_prop_list(&mut self, s: &str, syntax: SyntaxNode) {
     // Special case: no properties.
     if s == "normal" {
+      if self.flattened {
+        self
+          .diagnostics
+          .error(syntax, "`normal` is not a property list")
+          .hint("since 1.13, use \"\" for blocks without properties");
+      }
       return;
     }
 
     if s == "" {
-      self
-        .diagnostics
-        .error(syntax, "empty property list is not allowed")
-        .hint("use 'normal' instead");
+      if !self.flattened {
+        self
+          .diagnostics
+          .error(syntax, "empty property list is not allowed")
+          .hint("use 'normal' instead");
+      }
       return;
     }
 
@@ -168,9 +208,7 @@ impl Validator<'_> {
       }
 
       let key = prop.split('=').next().unwrap();
-      if key.is_empty() {
-        self.diagnostics.error(span, format!("invalid empty property key`"));
-      }
+      check_prop_key(self.diagnostics, span, key);
 
       if key < prev_key {
         self.diagnostics.error(span, format!("property keys must be in alphabetical order"));
@@ -181,23 +219,25 @@ impl Validator<'_> {
         self.diagnostics.error(span, format!("duplicate property key `{}`", key));
       }
 
-      if !key.chars().all(|c| matches!(c, 'a'..='z' | '_')) {
-        self
-          .diagnostics
-          .error(span, format!("invalid property key `{}`", key))
-          .hint("property keys may only contain lowercase letters");
-      }
-
       let value = prop.split('=').nth(1).unwrap();
-      if value.is_empty() {
-        self.diagnostics.error(span, format!("invalid empty property value"));
-      }
+      check_prop_value(self.diagnostics, span, value);
+    }
+  }
 
-      if !value.chars().all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_')) {
-        self
-          .diagnostics
-          .error(span, format!("invalid property value `{}`", value))
-          .hint("property values may only contain lowercase letters or numbers");
+  fn check_condition(&mut self, prop: &PropCondition, element: &ast::Element) {
+    if let Some(key) = element.key() {
+      check_prop_key(self.diagnostics, key.syntax().text_range(), &prop.key);
+    }
+
+    let Some(value) = element.value() else { return };
+    let mut seen = HashSet::new();
+    for (v, span) in condition_values(&value) {
+      // Empty values have an empty span, so underline the whole string instead.
+      let span = if v.is_empty() { value.syntax().text_range() } else { span };
+      check_prop_value(self.diagnostics, span, &v);
+
+      if !v.is_empty() && !seen.insert(v.clone()) {
+        self.diagnostics.warn(span, format!("duplicate property value `{}`", v));
       }
     }
   }
@@ -215,6 +255,58 @@ impl Validator<'_> {
   }
 }
 
+pub(crate) fn check_prop_key(diagnostics: &mut Diagnostics, span: TextRange, key: &str) {
+  if key.is_empty() {
+    diagnostics.error(span, format!("invalid empty property key`"));
+  }
+
+  if !key.chars().all(|c| matches!(c, 'a'..='z' | '_')) {
+    diagnostics
+      .error(span, format!("invalid property key `{}`", key))
+      .hint("property keys may only contain lowercase letters");
+  }
+}
+
+pub(crate) fn check_prop_value(diagnostics: &mut Diagnostics, span: TextRange, value: &str) {
+  if value.is_empty() {
+    diagnostics.error(span, format!("invalid empty property value"));
+  }
+
+  if !value.chars().all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_')) {
+    diagnostics
+      .error(span, format!("invalid property value `{}`", value))
+      .hint("property values may only contain lowercase letters or numbers");
+  }
+}
+
+/// Returns each `|` separated value of a multipart condition, along with its
+/// span. The leading `!` of a negated condition is skipped.
+pub fn condition_values(value: &ast::Value) -> Vec<(String, TextRange)> {
+  let range = value.syntax().text_range();
+  let Some(text) = value.as_str() else {
+    // Numbers and booleans are a single value.
+    return vec![(value.syntax().text().to_string(), range)];
+  };
+
+  // FIXME: Need to handle escapes.
+  let mut offset = range.start() + TextSize::from(1);
+  let text = match text.strip_prefix('!') {
+    Some(rest) => {
+      offset += TextSize::from(1);
+      rest
+    }
+    None => text.as_str(),
+  };
+
+  let mut values = vec![];
+  for v in text.split('|') {
+    let len = TextSize::from(v.len() as u32);
+    values.push((v.to_string(), TextRange::new(offset, offset + len)));
+    offset += len + TextSize::from(1);
+  }
+  values
+}
+
 pub struct PropIter<'a> {
   s:      &'a str,
   i:      u32,
@@ -1,4 +1,4 @@
-use mc_source::TextRange;
+use mc_source::{TextRange, TextSize};
 use mc_syntax::SyntaxNode;
 
 /// A collection of diagnostics in a single file.
@@ -83,6 +83,17 @@ impl Diagnostic {
     self.suggestion = Some(Suggestion::remove(title, span));
     self
   }
+
+  pub fn suggest_replace(
+    &mut self,
+    title: impl Into<String>,
+    span: TextRange,
+    replace: impl Into<String>,
+  ) -> &mut Self {
+    self.suggestion =
+      Some(Suggestion { title: title.into(), range: span, replace: replace.into() });
+    self
+  }
 }
 
 impl Suggestion {
@@ -91,6 +102,13 @@ impl Suggestion {
   }
 }
 
+/// Returns the first character of a range. Diagnostics for large nodes, like a
+/// whole object, only underline the first character, as underlining
+/// everything is too annoying.
+pub fn first_char(range: TextRange) -> TextRange {
+  TextRange::new(range.start(), range.start() + TextSize::from(1))
+}
+
 impl Spanned for TextRange {
   fn span(&self) -> TextRange { *self }
 }
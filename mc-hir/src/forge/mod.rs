@@ -0,0 +1,236 @@
+//! Forge's blockstate format, version 1. These are blockstates with
+//! `"forge_marker": 1`, which build up variants from `defaults` and a map of
+//! models for each property value.
+
+use std::{collections::HashMap, sync::Arc};
+
+mod parse;
+mod validate;
+
+use la_arena::{Arena, Idx};
+use mc_source::{FileId, ModelPath, Path};
+use mc_syntax::{ast, AstPtr};
+
+use crate::{diagnostic::Diagnostics, model::F64Eq, HirDatabase};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ForgeBlockstate {
+  pub nodes: Arena<Node>,
+
+  /// The `Node::Variant` that every other variant is applied on top of.
+  pub defaults: Option<NodeId>,
+  /// The property-wise variants, like `"facing": { "north": { ... } }`.
+  pub props:    Vec<Property>,
+  /// The fully-defined variants, like `"inventory": [{ ... }]`.
+  pub full:     Vec<FullVariant>,
+}
+
+pub type NodeId = Idx<Node>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Node {
+  Variant(Variant),
+  Model(Model),
+  TextureDef(TextureDef),
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ForgeBlockstateSourceMap {
+  pub ast_values:   HashMap<AstPtr<ast::Value>, NodeId>,
+  pub ast_elements: HashMap<AstPtr<ast::Element>, NodeId>,
+
+  pub variants:     HashMap<NodeId, AstPtr<ast::Value>>,
+  pub models:       HashMap<NodeId, AstPtr<ast::Value>>,
+  pub texture_defs: HashMap<NodeId, AstPtr<ast::Element>>,
+
+  /// The `variants` object.
+  pub variants_obj: Option<AstPtr<ast::Value>>,
+  /// The source of each `ForgeBlockstate::props`.
+  pub props:        Vec<AstPtr<ast::Element>>,
+  /// The source of each value in `Property::values`.
+  pub prop_values:  HashMap<NodeId, AstPtr<ast::Element>>,
+  /// The source of each `ForgeBlockstate::full`.
+  pub full:         Vec<AstPtr<ast::Element>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Property {
+  pub name:   String,
+  /// Each value of this property, and the `Node::Variant` it applies.
+  pub values: Vec<(String, NodeId)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FullVariant {
+  pub name:     String,
+  /// The `Node::Variant`s to pick from.
+  pub variants: Vec<NodeId>,
+}
+
+/// A partial variant. Any fields that aren't set are inherited from the
+/// variants it is applied on top of.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Variant {
+  pub model:     Option<NodeId>,
+  pub textures:  Vec<NodeId>,
+  pub x:         Option<F64Eq>,
+  pub y:         Option<F64Eq>,
+  pub uvlock:    Option<bool>,
+  pub weight:    Option<u32>,
+  /// Extra models rendered along with this one, by name.
+  pub submodels: Vec<(String, NodeId)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Model {
+  pub path: Path,
+}
+
+/// Overrides a texture variable in the model.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TextureDef {
+  pub name:  String,
+  pub value: String,
+}
+
+/// A single block state, after expanding the property-wise variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeState {
+  pub name:    String,
+  /// The `Node::Variant`s applied to this state, starting with `defaults`.
+  pub layers:  Vec<NodeId>,
+  /// The fully-defined variants to pick from, which are applied last.
+  pub choices: Vec<NodeId>,
+}
+
+impl ForgeBlockstate {
+  /// Expands the property-wise variants into every possible state, and merges
+  /// the fully-defined variants into them. Fully-defined variants which don't
+  /// match a property combination, like `inventory`, become their own state.
+  pub fn expand(&self) -> Vec<ForgeState> {
+    let mut props = self.props.iter().collect::<Vec<_>>();
+    props.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut states = vec![];
+    if !props.is_empty() && props.iter().all(|p| !p.values.is_empty()) {
+      let mut indices = vec![0; props.len()];
+      'outer: loop {
+        let mut names = vec![];
+        let mut layers = self.defaults.into_iter().collect::<Vec<_>>();
+        for (prop, &i) in props.iter().zip(indices.iter()) {
+          let (ref value, variant) = prop.values[i];
+          names.push(format!("{}={}", prop.name, value));
+          layers.push(variant);
+        }
+        states.push(ForgeState { name: names.join(","), layers, choices: vec![] });
+
+        for (i, prop) in indices.iter_mut().zip(props.iter()) {
+          *i += 1;
+          if *i < prop.values.len() {
+            continue 'outer;
+          }
+          *i = 0;
+        }
+        break;
+      }
+    }
+
+    for full in &self.full {
+      // Forge sorts the properties, so they may be written in any order.
+      let mut props = full.name.split(',').collect::<Vec<_>>();
+      props.sort();
+      let name = props.join(",");
+
+      match states.iter_mut().find(|s| s.name == name) {
+        Some(state) => state.choices.extend(full.variants.iter().copied()),
+        None => states.push(ForgeState {
+          name:    full.name.clone(),
+          layers:  self.defaults.into_iter().collect(),
+          choices: full.variants.clone(),
+        }),
+      }
+    }
+
+    states
+  }
+
+  /// Returns the `Node::Model` used by each choice of the given state. If the
+  /// state has no choices, this returns a single model.
+  pub fn models(&self, state: &ForgeState) -> Vec<Option<NodeId>> {
+    let base = state.layers.iter().rev().find_map(|&id| self.variant(id).model);
+    if state.choices.is_empty() {
+      return vec![base];
+    }
+
+    state.choices.iter().map(|&id| self.variant(id).model.or(base)).collect()
+  }
+
+  /// Returns the `Node::Model` that the given texture override applies to.
+  /// If the override is used by multiple states, this picks the first one.
+  pub fn model_for_texture(&self, texture: NodeId) -> Option<NodeId> {
+    let (id, variant) = self.nodes.iter().find_map(|(id, node)| match node {
+      Node::Variant(v) if v.textures.contains(&texture) => Some((id, v)),
+      _ => None,
+    })?;
+    if variant.model.is_some() {
+      return variant.model;
+    }
+
+    self.expand().iter().find_map(|state| {
+      if !state.layers.contains(&id) && !state.choices.contains(&id) {
+        return None;
+      }
+      self.models(state).into_iter().flatten().next()
+    })
+  }
+
+  pub fn variant(&self, id: NodeId) -> &Variant {
+    match self.nodes[id] {
+      Node::Variant(ref v) => v,
+      _ => panic!("expected a variant"),
+    }
+  }
+}
+
+/// Returns the model that a model path in a blockstate refers to. Like vanilla
+/// blockstates, these are relative to `models/block`.
+pub fn model_path(path: &Path) -> ModelPath {
+  let mut path = path.clone();
+  path.segments.insert(0, "block".into());
+  ModelPath { path }
+}
+
+/// Returns `true` if this blockstate uses the forge format.
+pub fn is_forge_blockstate(db: &dyn HirDatabase, file_id: FileId) -> bool {
+  let json = db.parse_json(file_id);
+  let Some(ast::Value::Object(obj)) = json.tree().value() else { return false };
+  obj.iter().any(|(key, _)| key.parse_text() == "forge_marker")
+}
+
+pub fn parse_forge_blockstate(
+  db: &dyn HirDatabase,
+  file_id: FileId,
+) -> (Arc<ForgeBlockstate>, Arc<ForgeBlockstateSourceMap>, Arc<Diagnostics>) {
+  let json = db.parse_json(file_id);
+
+  let mut diagnostics = Diagnostics::new();
+  let mut blockstate = ForgeBlockstate::default();
+  let mut source_map = ForgeBlockstateSourceMap::default();
+
+  let tree = json.tree();
+
+  parse::parse(&mut blockstate, &mut source_map, &mut diagnostics, &tree);
+
+  (Arc::new(blockstate), Arc::new(source_map), Arc::new(diagnostics))
+}
+
+pub fn validate_forge_blockstate(db: &dyn HirDatabase, file_id: FileId) -> Arc<Diagnostics> {
+  let json = db.parse_json(file_id);
+
+  let (_, source_map, diagnostics) = parse_forge_blockstate(db, file_id);
+  let mut diagnostics = (*diagnostics).clone();
+
+  validate::validate(db, file_id, &source_map, &json, &mut diagnostics);
+
+  Arc::new(diagnostics)
+}
@@ -0,0 +1,210 @@
+use crate::{diagnostic::Diagnostics, parse::Parser};
+use mc_syntax::{
+  ast::{self, AstNode},
+  AstPtr, Json,
+};
+
+use super::*;
+
+struct ForgeParser<'a> {
+  parser:     Parser<'a>,
+  blockstate: &'a mut ForgeBlockstate,
+  source_map: &'a mut ForgeBlockstateSourceMap,
+}
+
+pub fn parse(
+  blockstate: &mut ForgeBlockstate,
+  source_map: &mut ForgeBlockstateSourceMap,
+  diagnostics: &mut Diagnostics,
+  json: &Json,
+) {
+  let mut parser = ForgeParser { parser: Parser::new(json, diagnostics), blockstate, source_map };
+  parser.parse_root(json);
+}
+
+impl ForgeParser<'_> {
+  fn parse_root(&mut self, json: &ast::Json) {
+    let Some(root) = json.value() else { return };
+    let Some(obj) = self.parser.object(root) else { return };
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "forge_marker" => {
+          let Some(version) = self.parser.int(&value) else { continue };
+          if version != 1 {
+            self
+              .parser
+              .diagnostics
+              .error(value.syntax(), format!("unsupported forge blockstate version {version}"))
+              .hint("only version 1 is supported");
+          }
+        }
+        "defaults" => self.blockstate.defaults = self.parse_variant(value),
+        "variants" => {
+          self.source_map.variants_obj = Some(AstPtr::new(&value));
+          let Some(variants) = self.parser.object(value) else { continue };
+          for element in variants.elements() {
+            self.parse_variants_entry(element);
+          }
+        }
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+  }
+
+  /// Parses an entry of `variants`. This matches forge: arrays are always
+  /// fully-defined variants, and objects are a property if their first value is
+  /// an object.
+  fn parse_variants_entry(&mut self, element: ast::Element) {
+    let Some(name) = element.key().map(|k| k.parse_text()) else { return };
+    let Some(value) = element.value() else { return };
+
+    match value {
+      ast::Value::Array(ref arr) => {
+        let variants = arr.values().filter_map(|v| self.parse_variant(v)).collect();
+        self.source_map.full.push(AstPtr::new(&element));
+        self.blockstate.full.push(FullVariant { name, variants });
+      }
+      ast::Value::Object(ref obj) => {
+        let is_prop = obj
+          .elements()
+          .next()
+          .and_then(|e| e.value())
+          .is_some_and(|v| matches!(v, ast::Value::Object(_)));
+
+        if is_prop {
+          let mut values = vec![];
+          for value_element in obj.elements() {
+            let Some(key) = value_element.key().map(|k| k.parse_text()) else { continue };
+            let Some(v) = value_element.value() else { continue };
+            let Some(variant) = self.parse_variant(v) else { continue };
+
+            self.source_map.prop_values.insert(variant, AstPtr::new(&value_element));
+            values.push((key, variant));
+          }
+
+          self.source_map.props.push(AstPtr::new(&element));
+          self.blockstate.props.push(Property { name, values });
+        } else {
+          let variants = self.parse_variant(value.clone()).into_iter().collect();
+          self.source_map.full.push(AstPtr::new(&element));
+          self.blockstate.full.push(FullVariant { name, variants });
+        }
+      }
+      _ => {
+        self.parser.diagnostics.error(value.syntax(), "expected object or array");
+      }
+    }
+  }
+
+  fn parse_variant(&mut self, v: ast::Value) -> Option<NodeId> {
+    let mut variant = Variant::default();
+
+    let obj = self.parser.object(v.clone())?;
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "model" => variant.model = self.parse_model(value),
+        "textures" => {
+          let Some(textures) = self.parser.object(value) else { continue };
+          for element in textures.elements() {
+            let Some(name) = element.key().map(|k| k.parse_text()) else { continue };
+            let Some(value) = element.value() else { continue };
+            let Some(value) = self.parser.string(&value) else { continue };
+
+            variant.textures.push(self.alloc(element, TextureDef { name, value }));
+          }
+        }
+        "x" => variant.x = self.parser.float(&value).map(F64Eq),
+        "y" => variant.y = self.parser.float(&value).map(F64Eq),
+        "uvlock" => variant.uvlock = self.parser.bool(&value),
+        "weight" => variant.weight = self.weight(&value),
+        "submodel" => match value {
+          // A single submodel is just a model path.
+          ast::Value::StringValue(_) => {
+            if let Some(model) = self.parse_model(value) {
+              // This isn't in the source map, as its source is the model path.
+              let submodel = Variant { model: Some(model), ..Default::default() };
+              let submodel = self.blockstate.nodes.alloc(Node::Variant(submodel));
+              variant.submodels.push((String::new(), submodel));
+            }
+          }
+          _ => {
+            let Some(submodels) = self.parser.object(value) else { continue };
+            for element in submodels.elements() {
+              let Some(name) = element.key().map(|k| k.parse_text()) else { continue };
+              let Some(value) = element.value() else { continue };
+              if let Some(submodel) = self.parse_variant(value) {
+                variant.submodels.push((name, submodel));
+              }
+            }
+          }
+        },
+        // These are passed to the model loader or renderer, and we don't check them.
+        "transform" | "custom" | "smooth_lighting" | "gui3d" => {}
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    Some(self.alloc(v, variant))
+  }
+
+  fn parse_model(&mut self, m: ast::Value) -> Option<NodeId> {
+    let path = self.parser.string(&m)?;
+    let Ok(path) = path.parse() else {
+      self.parser.diagnostics.error(m.syntax(), "invalid model path");
+      return None;
+    };
+    Some(self.alloc(m, Model { path }))
+  }
+
+  fn weight(&mut self, value: &ast::Value) -> Option<u32> {
+    let n = self.parser.int(value)?;
+    match u32::try_from(n) {
+      Ok(n) if n > 0 => Some(n),
+      _ => {
+        self.parser.diagnostics.error(value.syntax(), "weight must be a positive integer");
+        None
+      }
+    }
+  }
+
+  fn alloc<T: ForgeNode>(&mut self, elem: T::Ast, node: T) -> NodeId { node.alloc(&elem, self) }
+}
+
+trait ForgeNode {
+  type Ast;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut ForgeParser) -> NodeId;
+}
+
+impl ForgeNode for Variant {
+  type Ast = ast::Value;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut ForgeParser) -> NodeId {
+    let id = parser.blockstate.nodes.alloc(Node::Variant(self));
+    parser.source_map.variants.insert(id, AstPtr::new(elem));
+    parser.source_map.ast_values.insert(AstPtr::new(elem), id);
+    id
+  }
+}
+
+impl ForgeNode for Model {
+  type Ast = ast::Value;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut ForgeParser) -> NodeId {
+    let id = parser.blockstate.nodes.alloc(Node::Model(self));
+    parser.source_map.models.insert(id, AstPtr::new(elem));
+    parser.source_map.ast_values.insert(AstPtr::new(elem), id);
+    id
+  }
+}
+
+impl ForgeNode for TextureDef {
+  type Ast = ast::Element;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut ForgeParser) -> NodeId {
+    let id = parser.blockstate.nodes.alloc(Node::TextureDef(self));
+    parser.source_map.texture_defs.insert(id, AstPtr::new(elem));
+    parser.source_map.ast_elements.insert(AstPtr::new(elem), id);
+    id
+  }
+}
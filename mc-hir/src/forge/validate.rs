@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use mc_source::{FileId, TextRange};
+use mc_syntax::{
+  ast::{self, AstNode},
+  Json, Parse,
+};
+
+use crate::{
+  blockstate::{check_prop_key, check_prop_value, PropIter},
+  diagnostic::{first_char, Diagnostics},
+  suggest, HirDatabase,
+};
+
+use super::{ForgeBlockstate, ForgeBlockstateSourceMap, Node};
+
+struct Validator<'a> {
+  db:         &'a dyn HirDatabase,
+  blockstate: &'a ForgeBlockstate,
+
+  source_map:  &'a ForgeBlockstateSourceMap,
+  json:        &'a Parse<Json>,
+  diagnostics: &'a mut Diagnostics,
+}
+
+pub fn validate(
+  db: &dyn HirDatabase,
+  file_id: FileId,
+  source_map: &ForgeBlockstateSourceMap,
+  json: &Parse<Json>,
+  diagnostics: &mut Diagnostics,
+) {
+  let blockstate = db.parse_forge_blockstate(file_id);
+  let mut validator = Validator { db, blockstate: &blockstate, source_map, json, diagnostics };
+  validator.validate_models();
+  validator.validate_props();
+  validator.validate_full_variants();
+  validator.validate_states();
+}
+
+impl Validator<'_> {
+  fn validate_models(&mut self) {
+    for (id, node) in self.blockstate.nodes.iter() {
+      let Node::Model(model) = node else { continue };
+      if self.db.lookup_model(super::model_path(&model.path)).is_none() {
+        let value = self.source_map.models[&id].tree(self.json);
+        suggest::missing_model(
+          self.db,
+          self.diagnostics,
+          &value,
+          &model.path.to_string(),
+          &["block"],
+        );
+      }
+    }
+  }
+
+  fn validate_props(&mut self) {
+    for (prop, ptr) in self.blockstate.props.iter().zip(self.source_map.props.iter()) {
+      let element = ast::Element::cast(ptr.to_node(self.json)).unwrap();
+      if let Some(key) = element.key() {
+        check_prop_key(self.diagnostics, key.syntax().text_range(), &prop.name);
+      }
+
+      for (value, id) in prop.values.iter() {
+        let element =
+          ast::Element::cast(self.source_map.prop_values[id].to_node(self.json)).unwrap();
+        if let Some(key) = element.key() {
+          check_prop_value(self.diagnostics, key.syntax().text_range(), value);
+        }
+      }
+    }
+  }
+
+  /// Checks that the fully-defined variants only use properties that are
+  /// defined by the property-wise variants.
+  fn validate_full_variants(&mut self) {
+    let props = self
+      .blockstate
+      .props
+      .iter()
+      .map(|p| (p.name.as_str(), p.values.iter().map(|(v, _)| v.as_str()).collect::<Vec<_>>()))
+      .collect::<HashMap<_, _>>();
+
+    for (full, ptr) in self.blockstate.full.iter().zip(self.source_map.full.iter()) {
+      // These are used for items and blocks without properties.
+      if full.name == "normal" || full.name == "inventory" {
+        continue;
+      }
+
+      let element = ast::Element::cast(ptr.to_node(self.json)).unwrap();
+      let Some(key) = element.key() else { continue };
+
+      for (prop, span) in PropIter::new(&full.name, key.syntax()) {
+        let Some((key, value)) = prop.split_once('=') else {
+          self
+            .diagnostics
+            .error(span, format!("invalid property `{}`", prop))
+            .hint("properties should be in the form `key=value`");
+          continue;
+        };
+
+        // Without any property-wise variants, the properties are only defined here.
+        if props.is_empty() {
+          check_prop_key(self.diagnostics, span, key);
+          check_prop_value(self.diagnostics, span, value);
+          continue;
+        }
+
+        match props.get(key) {
+          Some(values) if !values.contains(&value) => {
+            self
+              .diagnostics
+              .error(span, format!("property `{key}` has no value `{value}`"))
+              .hint(format!("`{key}` may be one of {}", values.join(", ")));
+          }
+          Some(_) => {}
+          None => {
+            self.diagnostics.error(span, format!("unknown property `{key}`"));
+          }
+        }
+      }
+    }
+  }
+
+  /// Checks that every expanded state has a model.
+  fn validate_states(&mut self) {
+    let full_spans = self
+      .blockstate
+      .full
+      .iter()
+      .zip(self.source_map.full.iter())
+      .map(|(full, ptr)| {
+        let element = ast::Element::cast(ptr.to_node(self.json)).unwrap();
+        let span = match element.key() {
+          Some(key) => key.syntax().text_range(),
+          None => element.syntax().text_range(),
+        };
+        (full.name.as_str(), span)
+      })
+      .collect::<HashMap<_, _>>();
+
+    let variants_span = match self.source_map.variants_obj {
+      Some(ref ptr) => first_char(ptr.to_node(self.json).text_range()),
+      None => first_char(TextRange::default()),
+    };
+
+    for state in self.blockstate.expand() {
+      if self.blockstate.models(&state).iter().all(|m| m.is_some()) {
+        continue;
+      }
+
+      let span = full_spans.get(state.name.as_str()).copied().unwrap_or(variants_span);
+      self
+        .diagnostics
+        .error(span, format!("no model for `{}`", state.name))
+        .hint("set a `model` in `defaults`, or in this variant");
+    }
+  }
+}
@@ -1,18 +1,32 @@
-use std::sync::Arc;
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
 
 use blockstate::Blockstate;
 use diagnostic::Diagnostics;
-use mc_source::{FileId, FileLocation, FileRange, ModelPath, ResolvedPath, SourceDatabase};
+use forge::ForgeBlockstate;
+use mc_source::{FileId, FileLocation, FileRange, ModelPath, SourceDatabase, TexturePath, Version};
 use mc_syntax::{
   ast::{self, AstNode},
   AstPtr, SyntaxToken, T,
 };
 use model::Model;
+use pack::PackMeta;
+use reference::ReferenceIndex;
+use symbol::SymbolIndex;
+use texture_meta::TextureMeta;
 
 pub mod blockstate;
 pub mod diagnostic;
+pub mod forge;
 pub mod model;
+pub mod pack;
 mod parse;
+pub mod reference;
+mod suggest;
+pub mod symbol;
+pub mod texture_meta;
 
 #[allow(unused_imports)]
 #[macro_use]
@@ -32,8 +46,29 @@ pub trait HirDatabase: SourceDatabase {
     file_id: FileId,
   ) -> (Arc<Blockstate>, Arc<blockstate::BlockstateSourceMap>, Arc<Diagnostics>);
 
+  #[salsa::invoke(forge::parse_forge_blockstate)]
+  fn parse_forge_blockstate_with_source_map(
+    &self,
+    file_id: FileId,
+  ) -> (Arc<ForgeBlockstate>, Arc<forge::ForgeBlockstateSourceMap>, Arc<Diagnostics>);
+
+  #[salsa::invoke(texture_meta::parse_texture_meta)]
+  fn parse_texture_meta_with_source_map(
+    &self,
+    file_id: FileId,
+  ) -> (Arc<TextureMeta>, Arc<texture_meta::TextureMetaSourceMap>, Arc<Diagnostics>);
+
+  #[salsa::invoke(pack::parse_pack_meta)]
+  fn parse_pack_meta_with_source_map(
+    &self,
+    file_id: FileId,
+  ) -> (Arc<PackMeta>, Arc<pack::PackMetaSourceMap>, Arc<Diagnostics>);
+
   fn parse_model(&self, file_id: FileId) -> Arc<Model>;
   fn parse_blockstate(&self, file_id: FileId) -> Arc<Blockstate>;
+  fn parse_forge_blockstate(&self, file_id: FileId) -> Arc<ForgeBlockstate>;
+  fn parse_texture_meta(&self, file_id: FileId) -> Arc<TextureMeta>;
+  fn parse_pack_meta(&self, file_id: FileId) -> Arc<PackMeta>;
 
   #[salsa::invoke(model::validate_model)]
   fn validate_model(&self, file_id: FileId) -> Arc<Diagnostics>;
@@ -41,12 +76,64 @@ pub trait HirDatabase: SourceDatabase {
   #[salsa::invoke(blockstate::validate_blockstate)]
   fn validate_blockstate(&self, file_id: FileId) -> Arc<Diagnostics>;
 
+  #[salsa::invoke(forge::validate_forge_blockstate)]
+  fn validate_forge_blockstate(&self, file_id: FileId) -> Arc<Diagnostics>;
+
+  /// Returns `true` if the given blockstate uses the forge format, and should
+  /// be parsed with `parse_forge_blockstate`.
+  #[salsa::invoke(forge::is_forge_blockstate)]
+  fn is_forge_blockstate(&self, file_id: FileId) -> bool;
+
+  #[salsa::invoke(texture_meta::validate_texture_meta)]
+  fn validate_texture_meta(&self, file_id: FileId) -> Arc<Diagnostics>;
+
+  #[salsa::invoke(pack::validate_pack_meta)]
+  fn validate_pack_meta(&self, file_id: FileId) -> Arc<Diagnostics>;
+
+  /// Returns the pack format of the project, from its `pack.mcmeta`.
+  #[salsa::invoke(pack::pack_format)]
+  fn pack_format(&self) -> Option<u32>;
+
+  /// Returns the minecraft version of the project. This comes from the pack
+  /// format if there is one, and the build scripts otherwise.
+  #[salsa::invoke(pack::minecraft_version)]
+  fn minecraft_version(&self) -> Option<Version>;
+
   fn lookup_model(&self, path: ModelPath) -> Option<FileId>;
+  fn lookup_texture(&self, path: TexturePath) -> Option<FileId>;
 
   /// Returns the ancestry, starting with the root, and ending with the child.
   #[salsa::invoke(model::ancestry)]
+  #[salsa::cycle(model::recover_ancestry)]
   fn model_ancestry(&self, file_id: FileId) -> Vec<FileId>;
 
+  /// Returns the models that a file uses, for finding leaf models.
+  #[salsa::invoke(model::file_model_uses)]
+  fn file_model_uses(&self, file_id: FileId) -> Arc<model::ModelUses>;
+  /// Returns the models used directly by blockstates and items, along with the
+  /// texture variables that blockstates override for each of them.
+  #[salsa::invoke(model::leaf_models)]
+  fn leaf_models(&self) -> Arc<HashMap<FileId, HashSet<String>>>;
+  /// Returns the models that directly inherit from each model.
+  #[salsa::invoke(model::model_children)]
+  fn model_children(&self) -> Arc<HashMap<FileId, Vec<FileId>>>;
+  /// Returns every model that inherits from the given model.
+  #[salsa::invoke(model::model_descendants)]
+  fn model_descendants(&self, file_id: FileId) -> Arc<Vec<FileId>>;
+  /// Returns the leaf models that inherit from the given model.
+  #[salsa::invoke(model::model_leaves)]
+  fn model_leaves(&self, file_id: FileId) -> Arc<Vec<FileId>>;
+
+  /// Returns the references in a file, along with their definitions.
+  #[salsa::invoke(reference::file_references)]
+  fn file_references(&self, file_id: FileId) -> Arc<Vec<(FileRange, FileRange)>>;
+  /// Returns every reference in the workspace.
+  #[salsa::invoke(reference::reference_index)]
+  fn reference_index(&self) -> Arc<ReferenceIndex>;
+  /// Returns every resource in the workspace, for searching.
+  #[salsa::invoke(symbol::symbol_index)]
+  fn symbol_index(&self) -> Arc<SymbolIndex>;
+
   fn model_def_at_index(&self, pos: FileLocation) -> Option<FileRange>;
   fn model_node_at_index(&self, pos: FileLocation) -> Option<model::NodeId>;
   fn model_def_at_node(&self, file: FileId, node: model::NodeId) -> Option<FileRange>;
@@ -54,6 +141,9 @@ pub trait HirDatabase: SourceDatabase {
   fn blockstate_def_at_index(&self, pos: FileLocation) -> Option<FileRange>;
   fn blockstate_node_at_index(&self, pos: FileLocation) -> Option<blockstate::NodeId>;
   fn blockstate_def_at_node(&self, file: FileId, node: blockstate::NodeId) -> Option<FileRange>;
+
+  fn forge_def_at_index(&self, pos: FileLocation) -> Option<FileRange>;
+  fn forge_node_at_index(&self, pos: FileLocation) -> Option<forge::NodeId>;
 }
 
 fn parse_model(db: &dyn HirDatabase, file_id: FileId) -> Arc<Model> {
@@ -62,20 +152,22 @@ fn parse_model(db: &dyn HirDatabase, file_id: FileId) -> Arc<Model> {
 fn parse_blockstate(db: &dyn HirDatabase, file_id: FileId) -> Arc<Blockstate> {
   db.parse_blockstate_with_source_map(file_id).0
 }
+fn parse_forge_blockstate(db: &dyn HirDatabase, file_id: FileId) -> Arc<ForgeBlockstate> {
+  db.parse_forge_blockstate_with_source_map(file_id).0
+}
+fn parse_texture_meta(db: &dyn HirDatabase, file_id: FileId) -> Arc<TextureMeta> {
+  db.parse_texture_meta_with_source_map(file_id).0
+}
+fn parse_pack_meta(db: &dyn HirDatabase, file_id: FileId) -> Arc<PackMeta> {
+  db.parse_pack_meta_with_source_map(file_id).0
+}
 
 fn lookup_model(db: &dyn HirDatabase, path: ModelPath) -> Option<FileId> {
-  let workspace = db.workspace();
-  let namespace = workspace.namespaces.iter().find(|n| n.name == path.path.namespace)?;
-
-  let search_path = ResolvedPath::Model(path);
+  db.lookup_path(path.file_path()).first().copied()
+}
 
-  namespace.files.iter().find_map(|f| {
-    if f.resolved_path().as_ref() == Some(&search_path) {
-      Some(f.id)
-    } else {
-      None
-    }
-  })
+fn lookup_texture(db: &dyn HirDatabase, path: TexturePath) -> Option<FileId> {
+  db.lookup_path(path.file_path()).first().copied()
 }
 
 fn model_node_at_index(db: &dyn HirDatabase, pos: FileLocation) -> Option<model::NodeId> {
@@ -104,6 +196,27 @@ fn blockstate_node_at_index(db: &dyn HirDatabase, pos: FileLocation) -> Option<b
       let ptr = AstPtr::new(&ast::Value::cast(node).unwrap());
       source_map.ast_models.get(&ptr).copied()
     }
+    k if ast::Element::can_cast(k) => {
+      let ptr = AstPtr::new(&ast::Element::cast(node).unwrap());
+      source_map.ast_props.get(&ptr).copied()
+    }
+    _ => None,
+  })
+}
+
+fn forge_node_at_index(db: &dyn HirDatabase, pos: FileLocation) -> Option<forge::NodeId> {
+  let token = token_at_offset(db, pos);
+  let (_, source_map, _) = db.parse_forge_blockstate_with_source_map(pos.file);
+
+  token.parent_ancestors().find_map(|node| match node.kind() {
+    k if ast::Value::can_cast(k) => {
+      let ptr = AstPtr::new(&ast::Value::cast(node).unwrap());
+      source_map.ast_values.get(&ptr).copied()
+    }
+    k if ast::Element::can_cast(k) => {
+      let ptr = AstPtr::new(&ast::Element::cast(node).unwrap());
+      source_map.ast_elements.get(&ptr).copied()
+    }
     _ => None,
   })
 }
@@ -172,19 +285,7 @@ fn model_def_at_node(db: &dyn HirDatabase, file: FileId, node: model::NodeId) ->
         return None;
       }
 
-      let search_path =
-        ResolvedPath::Texture(mc_source::TexturePath::new(t.value.parse().unwrap()));
-
-      let workspace = db.workspace();
-      let file = workspace.namespaces.iter().find_map(|n| {
-        n.files.iter().find_map(|f| {
-          if f.resolved_path().as_ref() == Some(&search_path) {
-            Some(f.id)
-          } else {
-            None
-          }
-        })
-      })?;
+      let file = db.lookup_texture(TexturePath::new(t.value.parse().unwrap()))?;
 
       Some(FileRange { file, range: None })
     }
@@ -202,11 +303,59 @@ fn blockstate_def_at_node(
 
   match blockstate.nodes[node] {
     blockstate::Node::Model(ref p) => {
-      let mut path = p.path.clone();
-      // Blockstates implicitly have this at the start.
-      path.segments.insert(0, "block".into());
+      let file = db.lookup_model(blockstate::model_path(db, &p.path))?;
+
+      Some(FileRange { file, range: None })
+    }
+
+    _ => None,
+  }
+}
+
+fn forge_def_at_index(db: &dyn HirDatabase, pos: FileLocation) -> Option<FileRange> {
+  let node = db.forge_node_at_index(pos)?;
+  let blockstate = db.parse_forge_blockstate(pos.file);
+
+  match blockstate.nodes[node] {
+    forge::Node::Model(ref m) => {
+      let file = db.lookup_model(forge::model_path(&m.path))?;
+
+      Some(FileRange { file, range: None })
+    }
+
+    forge::Node::TextureDef(ref t) => {
+      let ast = db.parse_json(pos.file);
+      let (_, source_map, _) = db.parse_forge_blockstate_with_source_map(pos.file);
+      let element = source_map.texture_defs[&node].tree(&ast);
 
-      let file = db.lookup_model(ModelPath { path })?;
+      // The key goes to the texture variable this overrides, in the model that
+      // the expanded states use.
+      if element.key().is_some_and(|k| k.syntax().text_range().contains_inclusive(pos.index)) {
+        let model = blockstate.model_for_texture(node)?;
+        let forge::Node::Model(ref m) = blockstate.nodes[model] else { unreachable!() };
+        let file = db.lookup_model(forge::model_path(&m.path))?;
+
+        for file in db.model_ancestry(file).into_iter().rev() {
+          let model = db.parse_model(file);
+          let def = model.texture_defs.iter().find(|&&id| {
+            matches!(model.nodes[id], model::Node::TextureDef(ref def) if def.name == t.name)
+          });
+          if let Some(def) = def {
+            let ast = db.parse_json(file);
+            let (_, source_map, _) = db.parse_model_with_source_map(file);
+            let element = source_map.texture_defs[def].tree(&ast);
+
+            return Some(FileRange { file, range: Some(element.syntax().text_range()) });
+          }
+        }
+
+        return None;
+      }
+
+      if t.value.starts_with('#') {
+        return None;
+      }
+      let file = db.lookup_texture(TexturePath::new(t.value.parse().ok()?))?;
 
       Some(FileRange { file, range: None })
     }
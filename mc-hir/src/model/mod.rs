@@ -1,13 +1,16 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
 
 mod parse;
 mod validate;
 
 use la_arena::{Arena, Idx};
-use mc_source::{FileId, ModelPath};
+use mc_source::{FileId, FileType, ModelPath, ResolvedPath};
 use mc_syntax::{ast, AstPtr};
 
-use crate::{diagnostic::Diagnostics, HirDatabase};
+use crate::{blockstate, diagnostic::Diagnostics, forge, HirDatabase};
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Model {
@@ -16,6 +19,8 @@ pub struct Model {
   pub nodes: Arena<Node>,
 
   pub texture_defs: Vec<NodeId>,
+  /// The `Node::Transform`s in `display`.
+  pub display:      Vec<NodeId>,
 }
 
 pub type NodeId = Idx<Node>;
@@ -27,6 +32,8 @@ pub enum Node {
   Texture(Texture),
   Element(Element),
   Face(Face),
+  Rotation(Rotation),
+  Transform(Transform),
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -39,6 +46,8 @@ pub struct ModelSourceMap {
   pub textures:     HashMap<NodeId, AstPtr<ast::Value>>,
   pub elements:     HashMap<NodeId, AstPtr<ast::Object>>,
   pub faces:        HashMap<NodeId, AstPtr<ast::Object>>,
+  pub rotations:    HashMap<NodeId, AstPtr<ast::Object>>,
+  pub transforms:   HashMap<NodeId, AstPtr<ast::Element>>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -59,9 +68,12 @@ pub enum Texture {
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Element {
-  pub from:  Pos,
-  pub to:    Pos,
-  pub faces: Faces,
+  /// A label that some editors write. Minecraft ignores it.
+  pub name:     Option<String>,
+  pub from:     Pos,
+  pub to:       Pos,
+  pub faces:    Faces,
+  pub rotation: Option<NodeId>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -74,10 +86,181 @@ pub struct Faces {
   pub down:  Option<NodeId>,
 }
 
+impl Faces {
+  /// Iterates over the faces that are set, along with their direction.
+  pub fn iter(&self) -> impl Iterator<Item = (Direction, NodeId)> {
+    [
+      (Direction::North, self.north),
+      (Direction::East, self.east),
+      (Direction::South, self.south),
+      (Direction::West, self.west),
+      (Direction::Up, self.up),
+      (Direction::Down, self.down),
+    ]
+    .into_iter()
+    .filter_map(|(dir, face)| Some((dir, face?)))
+  }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Face {
-  pub uv:      [F64Eq; 4],
-  pub texture: NodeId,
+  pub uv:        FaceUv,
+  pub texture:   NodeId,
+  /// The texture rotation, in degrees. One of 0, 90, 180, or 270.
+  pub rotation:  u32,
+  pub tintindex: Option<i32>,
+  pub cullface:  Option<Direction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaceUv {
+  /// The `uv` was written out in the face.
+  Explicit([F64Eq; 4]),
+  /// The face has no `uv`, so it is derived from the element's bounds.
+  Derived([F64Eq; 4]),
+}
+
+impl FaceUv {
+  pub fn coords(&self) -> [F64Eq; 4] {
+    match self {
+      FaceUv::Explicit(uv) | FaceUv::Derived(uv) => *uv,
+    }
+  }
+
+  pub fn is_derived(&self) -> bool { matches!(self, FaceUv::Derived(_)) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+  North,
+  East,
+  South,
+  West,
+  Up,
+  Down,
+}
+
+impl Direction {
+  /// Parses a direction. Like vanilla, this accepts `bottom` for `down`.
+  pub fn from_name(name: &str) -> Option<Direction> {
+    match name {
+      "north" => Some(Direction::North),
+      "east" => Some(Direction::East),
+      "south" => Some(Direction::South),
+      "west" => Some(Direction::West),
+      "up" => Some(Direction::Up),
+      "down" | "bottom" => Some(Direction::Down),
+      _ => None,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Direction::North => "north",
+      Direction::East => "east",
+      Direction::South => "south",
+      Direction::West => "west",
+      Direction::Up => "up",
+      Direction::Down => "down",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rotation {
+  pub origin:  Pos,
+  pub axis:    Axis,
+  pub angle:   F64Eq,
+  pub rescale: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+  X,
+  Y,
+  Z,
+}
+
+impl Element {
+  /// Returns the UV of a face without an explicit `uv`. This matches vanilla,
+  /// which projects the element's bounds onto the face.
+  pub fn derived_uv(&self, dir: Direction) -> [F64Eq; 4] {
+    let (from, to) = (self.from, self.to);
+    let uv = match dir {
+      Direction::Down => [from.x.0, 16.0 - to.z.0, to.x.0, 16.0 - from.z.0],
+      Direction::Up => [from.x.0, from.z.0, to.x.0, to.z.0],
+      Direction::North => [16.0 - to.x.0, 16.0 - to.y.0, 16.0 - from.x.0, 16.0 - from.y.0],
+      Direction::South => [from.x.0, 16.0 - to.y.0, to.x.0, 16.0 - from.y.0],
+      Direction::West => [from.z.0, 16.0 - to.y.0, to.z.0, 16.0 - from.y.0],
+      Direction::East => [16.0 - to.z.0, 16.0 - to.y.0, 16.0 - from.z.0, 16.0 - from.y.0],
+    };
+    uv.map(F64Eq)
+  }
+}
+
+impl Rotation {
+  /// Vanilla only allows these angles.
+  pub const ANGLES: [f64; 5] = [-45.0, -22.5, 0.0, 22.5, 45.0];
+}
+
+/// How the model is transformed when displayed in a given context, like in a
+/// player's hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transform {
+  pub context:     DisplayContext,
+  pub rotation:    Pos,
+  /// The translation, in 1/16ths of a block.
+  pub translation: Pos,
+  pub scale:       Pos,
+}
+
+impl Transform {
+  /// Vanilla clamps each component of the translation to this.
+  pub const MAX_TRANSLATION: f64 = 80.0;
+  /// Vanilla clamps each component of the scale to this.
+  pub const MAX_SCALE: f64 = 4.0;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DisplayContext {
+  ThirdpersonRighthand,
+  ThirdpersonLefthand,
+  FirstpersonRighthand,
+  FirstpersonLefthand,
+  Head,
+  Gui,
+  Ground,
+  Fixed,
+}
+
+impl DisplayContext {
+  pub const ALL: [DisplayContext; 8] = [
+    DisplayContext::ThirdpersonRighthand,
+    DisplayContext::ThirdpersonLefthand,
+    DisplayContext::FirstpersonRighthand,
+    DisplayContext::FirstpersonLefthand,
+    DisplayContext::Head,
+    DisplayContext::Gui,
+    DisplayContext::Ground,
+    DisplayContext::Fixed,
+  ];
+
+  pub fn from_name(name: &str) -> Option<DisplayContext> {
+    DisplayContext::ALL.into_iter().find(|c| c.name() == name)
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      DisplayContext::ThirdpersonRighthand => "thirdperson_righthand",
+      DisplayContext::ThirdpersonLefthand => "thirdperson_lefthand",
+      DisplayContext::FirstpersonRighthand => "firstperson_righthand",
+      DisplayContext::FirstpersonLefthand => "firstperson_lefthand",
+      DisplayContext::Head => "head",
+      DisplayContext::Gui => "gui",
+      DisplayContext::Ground => "ground",
+      DisplayContext::Fixed => "fixed",
+    }
+  }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
@@ -145,3 +328,278 @@ pub fn ancestry(db: &dyn HirDatabase, file: FileId) -> Vec<FileId> {
 
   ancestry
 }
+
+/// How a single file uses models. This is collected per file, so that editing
+/// one file doesn't require parsing every other file again.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ModelUses {
+  /// The models that a blockstate uses directly, along with the texture
+  /// variables that forge blockstates override for each of them.
+  pub models:  Vec<(FileId, HashSet<String>)>,
+  /// The parent of a model.
+  pub parent:  Option<FileId>,
+  /// `true` for models in `item/`, which items may use directly.
+  pub is_item: bool,
+}
+
+pub fn file_model_uses(db: &dyn HirDatabase, file: FileId) -> Arc<ModelUses> {
+  let mut uses = ModelUses::default();
+
+  match db.file_type(file) {
+    FileType::Blockstate if db.is_forge_blockstate(file) => {
+      let blockstate = db.parse_forge_blockstate(file);
+      let mut models = HashMap::<FileId, HashSet<String>>::new();
+      for (id, node) in blockstate.nodes.iter() {
+        let (path, texture) = match node {
+          forge::Node::Model(m) => (&m.path, None),
+          forge::Node::TextureDef(t) => {
+            let Some(model) = blockstate.model_for_texture(id) else { continue };
+            let forge::Node::Model(ref m) = blockstate.nodes[model] else { continue };
+            (&m.path, Some(t.name.clone()))
+          }
+          _ => continue,
+        };
+        let Some(model) = db.lookup_model(forge::model_path(path)) else { continue };
+        models.entry(model).or_default().extend(texture);
+      }
+      uses.models = models.into_iter().collect();
+      uses.models.sort_by_key(|(model, _)| *model);
+    }
+    FileType::Blockstate => {
+      for node in db.parse_blockstate(file).nodes.values() {
+        let blockstate::Node::Model(ref m) = node else { continue };
+        let Some(model) = db.lookup_model(blockstate::model_path(db, &m.path)) else { continue };
+        uses.models.push((model, HashSet::new()));
+      }
+    }
+    FileType::Model => {
+      uses.parent = db.parse_model(file).parent.clone().and_then(|p| db.lookup_model(p));
+      let workspace = db.workspace();
+      if let Some(ResolvedPath::Model(path)) =
+        workspace.file(file).and_then(|(_, f)| f.resolved_path())
+      {
+        uses.is_item = path.path.segments.first().is_some_and(|s| s == "item");
+      }
+    }
+    FileType::Texture | FileType::TextureMeta | FileType::PackMeta => {}
+  }
+
+  Arc::new(uses)
+}
+
+/// Returns every model that is used directly by a blockstate or an item, along
+/// with the texture variables that forge blockstates override for it. These are
+/// the models that must define every texture they use.
+pub fn leaf_models(db: &dyn HirDatabase) -> Arc<HashMap<FileId, HashSet<String>>> {
+  let workspace = db.workspace();
+  let mut leaves = HashMap::<FileId, HashSet<String>>::new();
+  let mut parents = HashSet::new();
+  let mut items = vec![];
+
+  for file in workspace.namespaces.iter().flat_map(|n| n.files.iter()) {
+    if !matches!(file.ty, FileType::Model | FileType::Blockstate) {
+      continue;
+    }
+
+    let uses = db.file_model_uses(file.id);
+    for (model, overrides) in &uses.models {
+      add_leaf(&mut leaves, *model, overrides.clone());
+    }
+    parents.extend(uses.parent);
+    if uses.is_item {
+      items.push(file.id);
+    }
+  }
+
+  // Item models that other models inherit from, like `item/handheld`, are
+  // templates, and aren't used by items directly.
+  for item in items {
+    if !parents.contains(&item) {
+      add_leaf(&mut leaves, item, HashSet::new());
+    }
+  }
+
+  Arc::new(leaves)
+}
+
+/// Returns the models that directly inherit from each model.
+pub fn model_children(db: &dyn HirDatabase) -> Arc<HashMap<FileId, Vec<FileId>>> {
+  let workspace = db.workspace();
+  let mut children = HashMap::<FileId, Vec<FileId>>::new();
+
+  for file in workspace.namespaces.iter().flat_map(|n| n.files.iter()) {
+    if file.ty != FileType::Model {
+      continue;
+    }
+    if let Some(parent) = db.file_model_uses(file.id).parent {
+      children.entry(parent).or_default().push(file.id);
+    }
+  }
+
+  Arc::new(children)
+}
+
+/// Returns every model that inherits from the given model, not including the
+/// model itself.
+pub fn model_descendants(db: &dyn HirDatabase, file: FileId) -> Arc<Vec<FileId>> {
+  let children = db.model_children();
+
+  let mut descendants = vec![];
+  let mut seen = HashSet::from([file]);
+  let mut stack = vec![file];
+  while let Some(model) = stack.pop() {
+    for &child in children.get(&model).into_iter().flatten() {
+      if seen.insert(child) {
+        descendants.push(child);
+        stack.push(child);
+      }
+    }
+  }
+  descendants.sort();
+
+  Arc::new(descendants)
+}
+
+/// Adds a use of a leaf model. Texture overrides only count if every use of the
+/// model has them.
+fn add_leaf(
+  leaves: &mut HashMap<FileId, HashSet<String>>,
+  model: FileId,
+  overrides: HashSet<String>,
+) {
+  match leaves.get_mut(&model) {
+    Some(existing) => existing.retain(|name| overrides.contains(name)),
+    None => {
+      leaves.insert(model, overrides);
+    }
+  }
+}
+
+/// Returns the leaf models that inherit from the given model. This includes the
+/// model itself, if it is a leaf.
+pub fn model_leaves(db: &dyn HirDatabase, file: FileId) -> Arc<Vec<FileId>> {
+  let leaf_models = db.leaf_models();
+
+  let mut leaves = std::iter::once(file)
+    .chain(db.model_descendants(file).iter().copied())
+    .filter(|model| leaf_models.contains_key(model))
+    .collect::<Vec<_>>();
+  leaves.sort();
+
+  Arc::new(leaves)
+}
+
+/// Returns `true` if the texture variable resolves to a texture in the given
+/// leaf model.
+pub fn resolves_texture(db: &dyn HirDatabase, leaf: FileId, name: &str) -> bool {
+  let chain = resolve_texture_var(db, leaf, name);
+  if chain.texture.is_some() {
+    return true;
+  }
+
+  // Forge blockstates may set any variable along the way.
+  let leaves = db.leaf_models();
+  leaves.get(&leaf).is_some_and(|o| chain.vars.iter().any(|(name, _)| o.contains(name)))
+}
+
+/// The variables that a texture variable refers to, up to the texture it
+/// resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureChain {
+  /// Every variable followed, starting with the given one, along with the
+  /// model that defines it. The last variable isn't defined when this is
+  /// `None`, and it repeats an earlier variable if the chain has a cycle.
+  pub vars:    Vec<(String, Option<FileId>)>,
+  /// The texture at the end of the chain, if there is one.
+  pub texture: Option<String>,
+}
+
+/// Follows a texture variable, as seen from the given model, until it resolves
+/// to a texture.
+pub fn resolve_texture_var(db: &dyn HirDatabase, file: FileId, name: &str) -> TextureChain {
+  let ancestry = db.model_ancestry(file);
+
+  let mut chain = TextureChain { vars: vec![], texture: None };
+  let mut seen = HashSet::new();
+  let mut name = name.to_string();
+  loop {
+    let def = find_texture_def(db, &ancestry, &name);
+    let cycle = !seen.insert(name.clone());
+    chain.vars.push((name, def.map(|(file, _)| file)));
+
+    let Some((file, id)) = def else { break };
+    if cycle {
+      break;
+    }
+
+    let model = db.parse_model(file);
+    let Node::TextureDef(ref def) = model.nodes[id] else { break };
+    match def.value.strip_prefix('#') {
+      Some(next) => name = next.to_string(),
+      None => {
+        chain.texture = Some(def.value.clone());
+        break;
+      }
+    }
+  }
+
+  chain
+}
+
+/// Finds the closest definition of a texture variable, starting from the end
+/// of the ancestry. Returns the model that defines it, and its node.
+pub fn find_texture_def(
+  db: &dyn HirDatabase,
+  ancestry: &[FileId],
+  name: &str,
+) -> Option<(FileId, NodeId)> {
+  ancestry.iter().rev().find_map(|&file| Some((file, db.parse_model(file).texture_def(name)?)))
+}
+
+impl Model {
+  /// Returns the definition of the given texture variable in this model.
+  pub fn texture_def(&self, name: &str) -> Option<NodeId> {
+    self
+      .texture_defs
+      .iter()
+      .copied()
+      .find(|&id| matches!(self.nodes[id], Node::TextureDef(ref def) if def.name == name))
+  }
+}
+
+/// Returns the path of the given model, like `block/stone`.
+pub fn model_name(db: &dyn HirDatabase, file: FileId) -> String {
+  match db.workspace().file(file).and_then(|(_, f)| f.resolved_path()) {
+    Some(ResolvedPath::Model(path)) => path.path.to_string(),
+    _ => "this model".to_string(),
+  }
+}
+
+/// Models that are their own ancestor are treated as having no parent. The
+/// cycle itself is reported by `validate_model`.
+pub fn recover_ancestry(_: &dyn HirDatabase, _: &[String], file: &FileId) -> Vec<FileId> {
+  vec![*file]
+}
+
+/// Returns the parents of this model, up to and including itself, if this model
+/// is its own ancestor.
+pub fn parent_cycle(db: &dyn HirDatabase, file: FileId) -> Option<Vec<ModelPath>> {
+  let mut cycle = vec![];
+  let mut visited = HashSet::new();
+
+  let mut current = file;
+  loop {
+    let parent = db.parse_model(current).parent.clone()?;
+    let next = db.lookup_model(parent.clone())?;
+    cycle.push(parent);
+
+    if next == file {
+      return Some(cycle);
+    }
+    // This model leads into a cycle, but isn't part of it.
+    if !visited.insert(next) {
+      return None;
+    }
+    current = next;
+  }
+}
@@ -1,4 +1,7 @@
-use crate::{diagnostic::Diagnostics, parse::Parser};
+use crate::{
+  diagnostic::{first_char, Diagnostics},
+  parse::Parser,
+};
 use la_arena::RawIdx;
 use mc_source::ModelPath;
 use mc_syntax::{
@@ -39,7 +42,7 @@ impl ModelParser<'_> {
         "textures" => self.parse_textures(value),
         "elements" => self.parse_elements(value),
         "gui_light" => {}
-        "display" => {}
+        "display" => self.parse_display(value),
         _ => self.parser.warn_unknown_key(key),
       }
     }
@@ -78,17 +81,135 @@ impl ModelParser<'_> {
     let obj = self.parser.object(e)?;
     for (key, value) in obj.iter() {
       match key.parse_text().as_str() {
+        "name" => element.name = self.parser.string(&value),
         "from" => element.from = self.parse_pos(value),
         "to" => element.to = self.parse_pos(value),
         "faces" => element.faces = self.parse_faces(value),
-        "rotation" => {}
+        "rotation" => element.rotation = self.parse_rotation(value),
         _ => self.parser.warn_unknown_key(key),
       }
     }
 
+    for (dir, id) in element.faces.iter() {
+      if let Node::Face(ref mut face) = self.model.nodes[id] {
+        if face.uv.is_derived() {
+          face.uv = FaceUv::Derived(element.derived_uv(dir));
+        }
+      }
+    }
+
     Some(self.alloc(obj, element))
   }
 
+  fn parse_rotation(&mut self, r: ast::Value) -> Option<NodeId> {
+    let mut origin = None;
+    let mut axis = None;
+    let mut angle = None;
+    let mut rescale = false;
+
+    let obj = self.parser.object(r)?;
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "origin" => origin = Some(self.parse_pos(value)),
+        "axis" => {
+          let Some(name) = self.parser.string(&value) else { continue };
+          axis = match name.as_str() {
+            "x" => Some(Axis::X),
+            "y" => Some(Axis::Y),
+            "z" => Some(Axis::Z),
+            _ => {
+              self
+                .parser
+                .diagnostics
+                .error(value.syntax(), format!("invalid axis `{name}`"))
+                .hint("the axis must be `x`, `y`, or `z`");
+              continue;
+            }
+          };
+        }
+        "angle" => {
+          angle = self.parser.float(&value);
+          if angle.is_some_and(|a| !Rotation::ANGLES.contains(&a)) {
+            self
+              .parser
+              .diagnostics
+              .error(value.syntax(), format!("invalid rotation angle {}", angle.unwrap()))
+              .hint("the angle must be between -45 and 45, in steps of 22.5");
+          }
+        }
+        "rescale" => rescale = self.parser.bool(&value).unwrap_or_default(),
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    let span = first_char(obj.syntax().text_range());
+    let has_key = |name: &str| obj.iter().any(|(key, _)| key.parse_text() == name);
+    for name in ["origin", "axis", "angle"] {
+      if !has_key(name) {
+        self.parser.diagnostics.error(span, format!("missing `{name}`"));
+      }
+    }
+
+    let rotation = Rotation { origin: origin?, axis: axis?, angle: angle?.into(), rescale };
+    Some(self.alloc(obj, rotation))
+  }
+
+  fn parse_display(&mut self, d: ast::Value) {
+    let Some(obj) = self.parser.object(d) else { return };
+    for element in obj.elements() {
+      let Some(key) = element.key() else { continue };
+      let Some(context) = DisplayContext::from_name(&key.parse_text()) else {
+        self.parser.warn_unknown_key(key);
+        continue;
+      };
+      let Some(value) = element.value() else { continue };
+      let Some(transform) = self.parse_transform(context, value) else { continue };
+
+      self.alloc(element, transform);
+    }
+  }
+
+  fn parse_transform(&mut self, context: DisplayContext, t: ast::Value) -> Option<Transform> {
+    let mut transform = Transform {
+      context,
+      rotation: Pos::default(),
+      translation: Pos::default(),
+      scale: Pos { x: 1.0.into(), y: 1.0.into(), z: 1.0.into() },
+    };
+
+    let obj = self.parser.object(t)?;
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "rotation" => transform.rotation = self.parse_pos(value),
+        "translation" => {
+          transform.translation =
+            self.parse_clamped_pos(value, "translation", Transform::MAX_TRANSLATION)
+        }
+        "scale" => transform.scale = self.parse_clamped_pos(value, "scale", Transform::MAX_SCALE),
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    Some(transform)
+  }
+
+  /// Parses a position, and clamps each component like vanilla does.
+  fn parse_clamped_pos(&mut self, p: ast::Value, name: &str, max: f64) -> Pos {
+    let pos = self.parse_pos(p.clone());
+    let clamp = |n: F64Eq| F64Eq(n.0.clamp(-max, max));
+    let clamped = Pos { x: clamp(pos.x), y: clamp(pos.y), z: clamp(pos.z) };
+
+    if clamped != pos {
+      self
+        .parser
+        .diagnostics
+        .warn(p.syntax(), format!("{name} is out of range"))
+        .hint(format!("each component is clamped to between -{max} and {max}"));
+    }
+
+    clamped
+  }
+
   fn parse_pos(&mut self, p: ast::Value) -> Pos {
     let mut pos = Pos::default();
 
@@ -141,8 +262,12 @@ impl ModelParser<'_> {
 
   fn parse_face(&mut self, f: ast::Value) -> Option<NodeId> {
     let mut face = Face {
-      uv:      [0.0.into(), 0.0.into(), 16.0.into(), 16.0.into()],
-      texture: NodeId::from_raw(RawIdx::from_u32(0)),
+      // This is filled in once the element's bounds are known.
+      uv:        FaceUv::Derived([0.0.into(), 0.0.into(), 16.0.into(), 16.0.into()]),
+      texture:   NodeId::from_raw(RawIdx::from_u32(0)),
+      rotation:  0,
+      tintindex: None,
+      cullface:  None,
     };
 
     let obj = self.parser.object(f)?;
@@ -150,16 +275,24 @@ impl ModelParser<'_> {
     for (key, value) in obj.iter() {
       match key.parse_text().as_str() {
         "uv" => {
-          if let Some(arr) = self.parser.array(value) {
-            for (i, item) in arr.values().enumerate() {
-              face.uv[i] = self.parser.float(&item).unwrap_or_default().into();
-            }
+          let Some(arr) = self.parser.array(value) else { continue };
+          if arr.values().count() != 4 {
+            self.parser.diagnostics.error(arr.syntax(), "expected 4 elements");
+            continue;
+          }
+
+          let mut uv = [F64Eq::default(); 4];
+          for (i, item) in arr.values().enumerate() {
+            uv[i] = self.parser.float(&item).unwrap_or_default().into();
           }
+          face.uv = FaceUv::Explicit(uv);
         }
         "rotation" => {
-          if let Some(n) = self.parser.int(&value) {
-            if n % 45 != 0 {
-              self.parser.diagnostics.error(value.syntax(), "rotation must be a multiple of 45");
+          let Some(n) = self.parser.int(&value) else { continue };
+          match n {
+            0 | 90 | 180 | 270 => face.rotation = n as u32,
+            _ => {
+              self.parser.diagnostics.error(value.syntax(), "rotation must be 0, 90, 180, or 270");
             }
           }
         }
@@ -170,10 +303,23 @@ impl ModelParser<'_> {
 
           face.texture = node;
         }
-        "cullface" => {}
+        "cullface" => {
+          let Some(name) = self.parser.string(&value) else { continue };
+          face.cullface = Direction::from_name(&name);
+          if face.cullface.is_none() {
+            self
+              .parser
+              .diagnostics
+              .error(value.syntax(), format!("invalid cullface `{name}`"))
+              .hint("the cullface must be `north`, `east`, `south`, `west`, `up`, or `down`");
+          }
+        }
         "tintindex" => {
-          // TODO: Store this, and then tint the thing green.
-          self.parser.int(&value);
+          let Some(n) = self.parser.int(&value) else { continue };
+          face.tintindex = i32::try_from(n).ok();
+          if face.tintindex.is_none() {
+            self.parser.diagnostics.error(value.syntax(), "tintindex is out of range");
+          }
         }
         _ => self.parser.warn_unknown_key(key),
       }
@@ -229,7 +375,28 @@ impl ModelNode for Face {
 
   fn alloc(self, elem: &Self::Ast, parser: &mut ModelParser) -> NodeId {
     let id = parser.model.nodes.alloc(Node::Face(self));
-    parser.source_map.elements.insert(id, AstPtr::new(&elem));
+    parser.source_map.faces.insert(id, AstPtr::new(elem));
+    id
+  }
+}
+
+impl ModelNode for Rotation {
+  type Ast = ast::Object;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut ModelParser) -> NodeId {
+    let id = parser.model.nodes.alloc(Node::Rotation(self));
+    parser.source_map.rotations.insert(id, AstPtr::new(elem));
+    id
+  }
+}
+
+impl ModelNode for Transform {
+  type Ast = ast::Element;
+
+  fn alloc(self, elem: &Self::Ast, parser: &mut ModelParser) -> NodeId {
+    let id = parser.model.nodes.alloc(Node::Transform(self));
+    parser.model.display.push(id);
+    parser.source_map.transforms.insert(id, AstPtr::new(elem));
     id
   }
 }
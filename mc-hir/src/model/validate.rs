@@ -1,16 +1,11 @@
 use ast::Json;
-use mc_source::ResolvedPath;
-use mc_syntax::Parse;
+use mc_source::TexturePath;
+use mc_syntax::{ast::AstNode, Parse};
 
-use crate::diagnostic::Diagnostics;
+use crate::{diagnostic::Diagnostics, suggest};
 
 use super::*;
 
-struct ModelValidator<'a> {
-  db:    &'a dyn HirDatabase,
-  model: Arc<Model>,
-}
-
 struct Validator<'a> {
   db:      &'a dyn HirDatabase,
   model:   &'a Model,
@@ -34,22 +29,42 @@ pub fn validate(
 }
 
 impl Validator<'_> {
-  fn model_validator(&self) -> ModelValidator {
-    ModelValidator { db: self.db, model: self.db.parse_model(self.file_id) }
-  }
-
   fn validate_model(&mut self) {
     for (id, node) in self.model.nodes.iter() {
       match node {
         Node::TextureDef(texture_def) => self.validate_texture_def(id, &texture_def),
         Node::Texture(texture) => self.validate_texture(id, &texture),
+        Node::Element(element) => self.validate_cullfaces(element),
+        Node::Parent(_) => self.validate_parent(id),
         _ => {}
       }
     }
   }
 
+  fn validate_parent(&mut self, id: NodeId) {
+    let Some(ref parent) = self.model.parent else { return };
+    if self.db.lookup_model(parent.clone()).is_none() {
+      let value = self.source_map.parent[&id].tree(self.json);
+      suggest::missing_model(self.db, self.diagnostics, &value, &parent.path.to_string(), &[]);
+      return;
+    }
+
+    let Some(cycle) = parent_cycle(self.db, self.file_id) else { return };
+
+    let this = model_name(self.db, self.file_id);
+    let cycle = cycle.iter().map(|p| format!("`{}`", p.path)).collect::<Vec<_>>().join(" -> ");
+
+    self
+      .diagnostics
+      .error(
+        self.source_map.parent[&id].to_node(self.json),
+        format!("model inherits from itself: `{this}` -> {cycle}"),
+      )
+      .hint("the parents of this model will be ignored");
+  }
+
   fn validate_texture_def(&mut self, id: NodeId, texture: &TextureDef) {
-    let is_used = self.model_validator().is_texture_def_used(&texture.name);
+    let is_used = self.is_texture_def_used(&texture.name);
 
     if !is_used {
       self.diagnostics.warn(
@@ -59,20 +74,7 @@ impl Validator<'_> {
     }
 
     if !texture.value.starts_with("#") {
-      let search_path =
-        ResolvedPath::Texture(mc_source::TexturePath::new(texture.value.parse().unwrap()));
-
-      // FIXME: Extract to a function.
-      let workspace = self.db.workspace();
-      let file = workspace.namespaces.iter().find_map(|n| {
-        n.files.iter().find_map(|f| {
-          if f.resolved_path().as_ref() == Some(&search_path) {
-            Some(f.id)
-          } else {
-            None
-          }
-        })
-      });
+      let file = self.db.lookup_texture(TexturePath::new(texture.value.parse().unwrap()));
 
       if file.is_none() {
         self.diagnostics.error(
@@ -83,51 +85,90 @@ impl Validator<'_> {
     }
   }
 
-  fn validate_texture(&mut self, id: NodeId, texture: &Texture) {
-    // FIXME: We only want to run this check on the leaf models, that are used by
-    // blockstates directly (as texture defs may exist in child models, which
-    // are then picked up by the parent model).
-    if false {
-      match texture {
-        Texture::Reference(name) => {
-          if !self.model.texture_defs.iter().any(|id| {
-            let texture_def = match self.model.nodes[*id] {
-              Node::TextureDef(ref texture_def) => texture_def,
-              _ => unreachable!(),
-            };
-
-            texture_def.name == *name
-          }) {
-            self.diagnostics.error(
-              self.source_map.textures[&id].to_node(&self.json),
-              format!("texture `{}` not found", name),
-            );
-          }
-        }
+  /// Checks that each cullface is the side of the block that its face is on.
+  /// Otherwise, the face will disappear when it shouldn't, or be rendered when
+  /// it's covered.
+  fn validate_cullfaces(&mut self, element: &Element) {
+    // Rotated faces aren't on the edge of the block, but this is common enough
+    // for small rotations that we don't warn about it.
+    if let Some(rotation) = element.rotation {
+      if matches!(self.model.nodes[rotation], Node::Rotation(ref r) if r.angle.0 != 0.0) {
+        return;
       }
     }
-  }
-}
 
-impl<'a> ModelValidator<'a> {
-  fn parent(&self) -> Option<ModelValidator<'a>> {
-    let parent = self.model.parent.clone()?;
+    for (dir, id) in element.faces.iter() {
+      let Node::Face(ref face) = self.model.nodes[id] else { continue };
+      let Some(cullface) = face.cullface else { continue };
+
+      let on_edge = match dir {
+        Direction::North => element.from.z.0.min(element.to.z.0) == 0.0,
+        Direction::South => element.from.z.0.max(element.to.z.0) == 16.0,
+        Direction::West => element.from.x.0.min(element.to.x.0) == 0.0,
+        Direction::East => element.from.x.0.max(element.to.x.0) == 16.0,
+        Direction::Down => element.from.y.0.min(element.to.y.0) == 0.0,
+        Direction::Up => element.from.y.0.max(element.to.y.0) == 16.0,
+      };
+      if on_edge && cullface == dir {
+        continue;
+      }
 
-    Some(ModelValidator {
-      db:    self.db,
-      model: self.db.parse_model(self.db.lookup_model(parent)?),
-    })
+      let obj = self.source_map.faces[&id].tree(self.json);
+      let Some((_, value)) = obj.iter().find(|(key, _)| key.parse_text() == "cullface") else {
+        continue;
+      };
+
+      if on_edge {
+        self
+          .diagnostics
+          .warn(
+            value.syntax(),
+            format!("cullface `{}` doesn't match the side this face is on", cullface.name()),
+          )
+          .hint(format!("this face is on the `{}` side of the block", dir.name()));
+      } else {
+        self.diagnostics.warn(value.syntax(), "this face isn't on the edge of the block").hint(
+          format!("the face will be hidden whenever the `{}` side is covered", cullface.name()),
+        );
+      }
+    }
   }
 
+  fn validate_texture(&mut self, id: NodeId, texture: &Texture) {
+    let Texture::Reference(name) = texture;
+
+    // Parent models may leave textures for their children to define, so this is
+    // only checked in the models that blockstates and items use.
+    let leaves = self.db.model_leaves(self.file_id);
+    let Some(&leaf) = leaves.iter().find(|&&leaf| !resolves_texture(self.db, leaf, name)) else {
+      return;
+    };
+
+    let diagnostic = self.diagnostics.error(
+      self.source_map.textures[&id].to_node(self.json),
+      format!("texture `#{name}` is not defined"),
+    );
+    if leaf != self.file_id {
+      diagnostic.hint(format!(
+        "`{}` inherits from this model, but doesn't define `{name}`",
+        model_name(self.db, leaf)
+      ));
+    }
+  }
+
+  /// Returns `true` if this model or any of its parents use the given texture
+  /// variable.
   fn is_texture_def_used(&self, name: &str) -> bool {
     if name == "particle" {
       return true;
     }
 
-    self.model.nodes.values().any(|node| match node {
-      Node::Texture(Texture::Reference(n)) => name == *n,
-      Node::TextureDef(def) => def.value.strip_prefix("#") == Some(name),
-      _ => false,
-    }) || self.parent().is_some_and(|p| p.is_texture_def_used(name))
+    self.db.model_ancestry(self.file_id).into_iter().any(|file| {
+      self.db.parse_model(file).nodes.values().any(|node| match node {
+        Node::Texture(Texture::Reference(n)) => name == *n,
+        Node::TextureDef(def) => def.value.strip_prefix("#") == Some(name),
+        _ => false,
+      })
+    })
   }
 }
@@ -0,0 +1,106 @@
+//! The `pack.mcmeta` file, at the root of a resource pack.
+
+use std::sync::Arc;
+
+mod parse;
+mod validate;
+
+use mc_source::{FileId, Version};
+use mc_syntax::{ast, AstPtr};
+
+use crate::{diagnostic::Diagnostics, HirDatabase};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PackMeta {
+  pub pack:     Option<Pack>,
+  pub overlays: Vec<Overlay>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PackMetaSourceMap {
+  pub pack:              Option<AstPtr<ast::Value>>,
+  pub pack_format:       Option<AstPtr<ast::Value>>,
+  pub supported_formats: Option<AstPtr<ast::Value>>,
+  pub overlays:          Option<AstPtr<ast::Value>>,
+
+  /// The `formats` of each overlay in `PackMeta::overlays`.
+  pub overlay_formats: Vec<Option<AstPtr<ast::Value>>>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Pack {
+  pub pack_format:       Option<u32>,
+  pub description:       Option<Description>,
+  pub supported_formats: Option<FormatRange>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Description {
+  Text(String),
+  /// A JSON text component. We don't look into these.
+  Component,
+}
+
+/// An inclusive range of pack formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatRange {
+  pub min: u32,
+  pub max: u32,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Overlay {
+  pub formats:   Option<FormatRange>,
+  pub directory: Option<String>,
+}
+
+impl FormatRange {
+  pub fn contains(&self, format: u32) -> bool { self.min <= format && format <= self.max }
+}
+
+pub fn parse_pack_meta(
+  db: &dyn HirDatabase,
+  file_id: FileId,
+) -> (Arc<PackMeta>, Arc<PackMetaSourceMap>, Arc<Diagnostics>) {
+  let json = db.parse_json(file_id);
+
+  let mut diagnostics = Diagnostics::new();
+  let mut meta = PackMeta::default();
+  let mut source_map = PackMetaSourceMap::default();
+
+  let tree = json.tree();
+
+  parse::parse(&mut meta, &mut source_map, &mut diagnostics, &tree);
+
+  (Arc::new(meta), Arc::new(source_map), Arc::new(diagnostics))
+}
+
+pub fn validate_pack_meta(db: &dyn HirDatabase, file_id: FileId) -> Arc<Diagnostics> {
+  let json = db.parse_json(file_id);
+
+  let (_, source_map, diagnostics) = parse_pack_meta(db, file_id);
+  let mut diagnostics = (*diagnostics).clone();
+
+  validate::validate(db, file_id, &source_map, &json, &mut diagnostics);
+
+  Arc::new(diagnostics)
+}
+
+pub fn pack_format(db: &dyn HirDatabase) -> Option<u32> {
+  let workspace = db.workspace();
+
+  workspace.packs.iter().find_map(|&pack| db.parse_pack_meta(pack).pack.as_ref()?.pack_format)
+}
+
+pub fn minecraft_version(db: &dyn HirDatabase) -> Option<Version> {
+  let configured = db.workspace().minecraft_version;
+
+  match db.pack_format() {
+    // The configured version is more precise, so prefer it if the two agree.
+    Some(format) => match configured {
+      Some(v) if v.pack_format() == Some(format) => Some(v),
+      _ => Version::from_pack_format(format).or(configured),
+    },
+    None => configured,
+  }
+}
@@ -0,0 +1,206 @@
+use crate::{
+  diagnostic::{first_char, Diagnostics},
+  parse::Parser,
+};
+use mc_syntax::{
+  ast::{self, AstNode},
+  AstPtr, Json,
+};
+
+use super::*;
+
+struct PackMetaParser<'a> {
+  parser:     Parser<'a>,
+  meta:       &'a mut PackMeta,
+  source_map: &'a mut PackMetaSourceMap,
+}
+
+pub fn parse(
+  meta: &mut PackMeta,
+  source_map: &mut PackMetaSourceMap,
+  diagnostics: &mut Diagnostics,
+  json: &Json,
+) {
+  let mut parser = PackMetaParser { parser: Parser::new(json, diagnostics), meta, source_map };
+  parser.parse_root(json);
+}
+
+impl PackMetaParser<'_> {
+  fn parse_root(&mut self, json: &ast::Json) {
+    let Some(root) = json.value() else { return };
+    let Some(obj) = self.parser.object(root) else { return };
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "pack" => {
+          self.source_map.pack = Some(AstPtr::new(&value));
+          self.meta.pack = self.parse_pack(value);
+        }
+        "overlays" => {
+          self.source_map.overlays = Some(AstPtr::new(&value));
+          self.parse_overlays(value);
+        }
+        // These don't affect models or textures, so we don't check them.
+        "language" | "filter" | "features" => {}
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    if self.source_map.pack.is_none() {
+      self
+        .parser
+        .diagnostics
+        .error(first_char(obj.syntax().text_range()), "missing `pack` section");
+    }
+  }
+
+  fn parse_pack(&mut self, p: ast::Value) -> Option<Pack> {
+    let mut pack = Pack::default();
+
+    let obj = self.parser.object(p)?;
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "pack_format" => {
+          self.source_map.pack_format = Some(AstPtr::new(&value));
+          pack.pack_format = self.format(&value);
+        }
+        "description" => {
+          pack.description = match value {
+            ast::Value::StringValue(_) => value.as_str().map(Description::Text),
+            ast::Value::Object(_) | ast::Value::Array(_) => Some(Description::Component),
+            _ => {
+              self.parser.diagnostics.error(value.syntax(), "expected a string or text component");
+              None
+            }
+          }
+        }
+        "supported_formats" => {
+          self.source_map.supported_formats = Some(AstPtr::new(&value));
+          pack.supported_formats = self.parse_range(&value);
+        }
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    let span = first_char(obj.syntax().text_range());
+    if self.source_map.pack_format.is_none() {
+      self.parser.diagnostics.error(span, "missing `pack_format`");
+    }
+    if !obj.iter().any(|(key, _)| key.parse_text() == "description") {
+      self.parser.diagnostics.error(span, "missing `description`");
+    }
+
+    Some(pack)
+  }
+
+  fn parse_overlays(&mut self, o: ast::Value) {
+    let Some(obj) = self.parser.object(o) else { return };
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "entries" => {
+          let Some(arr) = self.parser.array(value) else { continue };
+          for entry in arr.values() {
+            self.parse_overlay(entry);
+          }
+        }
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+  }
+
+  fn parse_overlay(&mut self, e: ast::Value) {
+    let mut overlay = Overlay::default();
+    let mut formats_ptr = None;
+
+    let Some(obj) = self.parser.object(e) else { return };
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "formats" => {
+          formats_ptr = Some(AstPtr::new(&value));
+          overlay.formats = self.parse_range(&value);
+        }
+        "directory" => {
+          let Some(directory) = self.parser.string(&value) else { continue };
+          if directory.is_empty()
+            || !directory.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+          {
+            self
+              .parser
+              .diagnostics
+              .error(value.syntax(), format!("invalid overlay directory `{directory}`"))
+              .hint("directories may only contain letters, numbers, `-`, `_`, and `.`");
+          }
+          overlay.directory = Some(directory);
+        }
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    let span = first_char(obj.syntax().text_range());
+    if formats_ptr.is_none() {
+      self.parser.diagnostics.error(span, "missing `formats`");
+    }
+    if overlay.directory.is_none() {
+      self.parser.diagnostics.error(span, "missing `directory`");
+    }
+
+    self.source_map.overlay_formats.push(formats_ptr);
+    self.meta.overlays.push(overlay);
+  }
+
+  /// Parses a format range, which may be a single format, `[min, max]`, or
+  /// `{ "min_inclusive": min, "max_inclusive": max }`.
+  fn parse_range(&mut self, r: &ast::Value) -> Option<FormatRange> {
+    let range = match r {
+      ast::Value::NumberValue(_) => {
+        let format = self.format(r)?;
+        FormatRange { min: format, max: format }
+      }
+      ast::Value::Array(arr) => {
+        let values = arr.values().collect::<Vec<_>>();
+        if values.len() != 2 {
+          self.parser.diagnostics.error(r.syntax(), "expected 2 elements");
+          return None;
+        }
+        FormatRange { min: self.format(&values[0])?, max: self.format(&values[1])? }
+      }
+      ast::Value::Object(obj) => {
+        let mut min = None;
+        let mut max = None;
+        for (key, value) in obj.iter() {
+          match key.parse_text().as_str() {
+            "min_inclusive" => min = self.format(&value),
+            "max_inclusive" => max = self.format(&value),
+            _ => self.parser.warn_unknown_key(key),
+          }
+        }
+        if min.is_none() || max.is_none() {
+          let span = first_char(obj.syntax().text_range());
+          self.parser.diagnostics.error(span, "expected `min_inclusive` and `max_inclusive`");
+        }
+        FormatRange { min: min?, max: max? }
+      }
+      _ => {
+        self.parser.diagnostics.error(r.syntax(), "expected a pack format or range");
+        return None;
+      }
+    };
+
+    if range.min > range.max {
+      self.parser.diagnostics.error(r.syntax(), "minimum format is greater than the maximum");
+      return None;
+    }
+
+    Some(range)
+  }
+
+  fn format(&mut self, value: &ast::Value) -> Option<u32> {
+    let n = self.parser.int(value)?;
+    match u32::try_from(n) {
+      Ok(n) if n > 0 => Some(n),
+      _ => {
+        self.parser.diagnostics.error(value.syntax(), "expected a positive integer");
+        None
+      }
+    }
+  }
+}
@@ -0,0 +1,102 @@
+use mc_source::{FileId, Version};
+use mc_syntax::{Json, Parse};
+
+use crate::{
+  diagnostic::{first_char, Diagnostics},
+  HirDatabase,
+};
+
+use super::{Pack, PackMeta, PackMetaSourceMap};
+
+struct Validator<'a> {
+  meta: &'a PackMeta,
+
+  source_map:  &'a PackMetaSourceMap,
+  json:        &'a Parse<Json>,
+  diagnostics: &'a mut Diagnostics,
+}
+
+pub fn validate(
+  db: &dyn HirDatabase,
+  file_id: FileId,
+  source_map: &PackMetaSourceMap,
+  json: &Parse<Json>,
+  diagnostics: &mut Diagnostics,
+) {
+  let meta = db.parse_pack_meta(file_id);
+  let mut validator = Validator { meta: &meta, source_map, json, diagnostics };
+
+  let Some(ref pack) = meta.pack else { return };
+  let Some(format) = pack.pack_format else { return };
+
+  validator.validate_format(format, db.workspace().minecraft_version);
+  validator.validate_supported_formats(pack, format);
+  validator.validate_overlays(format);
+}
+
+impl Validator<'_> {
+  fn validate_format(&mut self, format: u32, configured: Option<Version>) {
+    let Some(ref ptr) = self.source_map.pack_format else { return };
+    let span = ptr.to_node(self.json);
+
+    if !Version::is_release_pack_format(format) {
+      let diagnostic =
+        self.diagnostics.warn(&span, format!("pack format {format} isn't used by any release"));
+      if let Some(version) = Version::from_pack_format(format) {
+        diagnostic.hint(format!("the closest release is minecraft {version}"));
+      }
+    }
+
+    // Check that the pack format matches the version from the build scripts.
+    let Some(configured) = configured else { return };
+    let Some(expected) = configured.pack_format() else { return };
+    let supported = match self.meta.pack.as_ref().and_then(|p| p.supported_formats) {
+      Some(range) => range.contains(expected),
+      None => false,
+    };
+    if expected != format && !supported {
+      self
+        .diagnostics
+        .warn(&span, format!("pack format {format} does not match minecraft {configured}"))
+        .hint(format!("minecraft {configured} uses pack format {expected}"));
+    }
+  }
+
+  fn validate_supported_formats(&mut self, pack: &Pack, format: u32) {
+    let Some(range) = pack.supported_formats else { return };
+    let Some(ref ptr) = self.source_map.supported_formats else { return };
+    let span = ptr.to_node(self.json);
+
+    if !supports_overlays(format) {
+      self
+        .diagnostics
+        .warn(&span, "`supported_formats` is ignored before pack format 18")
+        .hint(format!("pack format 18 is used by minecraft {}", Version::OVERLAYS));
+    }
+
+    if !range.contains(format) {
+      self
+        .diagnostics
+        .error(&span, format!("`supported_formats` does not include the pack format {format}"));
+    }
+  }
+
+  fn validate_overlays(&mut self, format: u32) {
+    let Some(ref ptr) = self.source_map.overlays else { return };
+
+    if !supports_overlays(format) {
+      // The overlays can be quite long, so only underline the first character.
+      self
+        .diagnostics
+        .warn(
+          first_char(ptr.to_node(self.json).text_range()),
+          "overlays are ignored before pack format 18",
+        )
+        .hint(format!("pack format 18 is used by minecraft {}", Version::OVERLAYS));
+    }
+  }
+}
+
+fn supports_overlays(format: u32) -> bool {
+  Version::from_pack_format(format) >= Some(Version::OVERLAYS)
+}
@@ -0,0 +1,173 @@
+//! An index of every reference in the workspace, for finding references.
+
+use std::{collections::HashMap, sync::Arc};
+
+use mc_source::{FileId, FileRange, FileType, TextSize, TexturePath};
+use mc_syntax::ast::AstNode;
+
+use crate::{blockstate, forge, model, HirDatabase};
+
+/// Maps definitions to the places that refer to them. Models and textures are
+/// defined by a whole file (with a `range` of `None`), and texture variables
+/// are defined by their element in `textures`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReferenceIndex {
+  references:  HashMap<FileRange, Vec<FileRange>>,
+  definitions: HashMap<FileId, Vec<(FileRange, FileRange)>>,
+}
+
+impl ReferenceIndex {
+  /// Returns every reference to the given definition.
+  pub fn references(&self, def: FileRange) -> &[FileRange] {
+    self.references.get(&def).map(|r| r.as_slice()).unwrap_or_default()
+  }
+
+  /// Returns the definitions of the reference at the given location. Texture
+  /// variables used in a parent model may have a definition in each child.
+  pub fn definitions_at(&self, file: FileId, index: TextSize) -> Vec<FileRange> {
+    let Some(refs) = self.definitions.get(&file) else { return vec![] };
+
+    refs
+      .iter()
+      .filter(|(r, _)| r.range.is_some_and(|r| r.contains_inclusive(index)))
+      .map(|(_, def)| *def)
+      .collect()
+  }
+}
+
+pub fn reference_index(db: &dyn HirDatabase) -> Arc<ReferenceIndex> {
+  let mut index = ReferenceIndex::default();
+
+  let workspace = db.workspace();
+  for file in workspace.namespaces.iter().flat_map(|n| n.files.iter()) {
+    if !file.ty.is_json() {
+      continue;
+    }
+
+    for &(def, reference) in db.file_references(file.id).iter() {
+      let refs = index.references.entry(def).or_default();
+      // Texture variables in parents are found from each child.
+      if refs.contains(&reference) {
+        continue;
+      }
+      refs.push(reference);
+      index.definitions.entry(reference.file).or_default().push((reference, def));
+    }
+  }
+
+  Arc::new(index)
+}
+
+/// Returns the references that the given file makes, along with what they refer
+/// to. These are `(definition, reference)` pairs.
+pub fn file_references(db: &dyn HirDatabase, file: FileId) -> Arc<Vec<(FileRange, FileRange)>> {
+  let mut refs = vec![];
+  match db.file_type(file) {
+    FileType::Model => model_references(db, file, &mut refs),
+    FileType::Blockstate if db.is_forge_blockstate(file) => forge_references(db, file, &mut refs),
+    FileType::Blockstate => blockstate_references(db, file, &mut refs),
+    FileType::Texture | FileType::TextureMeta | FileType::PackMeta => {}
+  }
+
+  Arc::new(refs)
+}
+
+fn model_references(db: &dyn HirDatabase, file: FileId, refs: &mut Vec<(FileRange, FileRange)>) {
+  let json = db.parse_json(file);
+  let (model, source_map, _) = db.parse_model_with_source_map(file);
+
+  for (id, node) in model.nodes.iter() {
+    match node {
+      model::Node::Parent(p) => {
+        let Some(parent) = db.lookup_model(p.path.clone()) else { continue };
+        let range = source_map.parent[&id].to_node(&json).text_range();
+        refs
+          .push((FileRange { file: parent, range: None }, FileRange { file, range: Some(range) }));
+      }
+      model::Node::TextureDef(def) if !def.value.starts_with('#') => {
+        let Ok(path) = def.value.parse() else { continue };
+        let Some(texture) = db.lookup_texture(TexturePath::new(path)) else { continue };
+        let Some(value) = source_map.texture_defs[&id].tree(&json).value() else { continue };
+        let range = value.syntax().text_range();
+        refs
+          .push((FileRange { file: texture, range: None }, FileRange { file, range: Some(range) }));
+      }
+      _ => {}
+    }
+  }
+
+  // Texture variables resolve to the closest definition, starting from this
+  // model. This includes the variables used by parents.
+  let ancestry = db.model_ancestry(file);
+  for &user in ancestry.iter() {
+    let json = db.parse_json(user);
+    let (model, source_map, _) = db.parse_model_with_source_map(user);
+
+    for (id, node) in model.nodes.iter() {
+      let (name, range) = match node {
+        model::Node::Texture(model::Texture::Reference(name)) => {
+          (name.as_str(), source_map.textures[&id].to_node(&json).text_range())
+        }
+        model::Node::TextureDef(def) => {
+          let Some(name) = def.value.strip_prefix('#') else { continue };
+          let Some(value) = source_map.texture_defs[&id].tree(&json).value() else { continue };
+          (name, value.syntax().text_range())
+        }
+        _ => continue,
+      };
+
+      let Some(def) = texture_def(db, &ancestry, name) else { continue };
+      refs.push((def, FileRange { file: user, range: Some(range) }));
+    }
+  }
+}
+
+/// Finds the definition of a texture variable, starting from the end of the
+/// ancestry.
+fn texture_def(db: &dyn HirDatabase, ancestry: &[FileId], name: &str) -> Option<FileRange> {
+  let (file, id) = model::find_texture_def(db, ancestry, name)?;
+
+  let (_, source_map, _) = db.parse_model_with_source_map(file);
+  let range = source_map.texture_defs[&id].to_node(&db.parse_json(file)).text_range();
+  Some(FileRange { file, range: Some(range) })
+}
+
+fn blockstate_references(
+  db: &dyn HirDatabase,
+  file: FileId,
+  refs: &mut Vec<(FileRange, FileRange)>,
+) {
+  let json = db.parse_json(file);
+  let (blockstate, source_map, _) = db.parse_blockstate_with_source_map(file);
+
+  for (id, node) in blockstate.nodes.iter() {
+    let blockstate::Node::Model(m) = node else { continue };
+    let Some(model) = db.lookup_model(blockstate::model_path(db, &m.path)) else { continue };
+    let range = source_map.models[&id].to_node(&json).text_range();
+    refs.push((FileRange { file: model, range: None }, FileRange { file, range: Some(range) }));
+  }
+}
+
+fn forge_references(db: &dyn HirDatabase, file: FileId, refs: &mut Vec<(FileRange, FileRange)>) {
+  let json = db.parse_json(file);
+  let (blockstate, source_map, _) = db.parse_forge_blockstate_with_source_map(file);
+
+  for (id, node) in blockstate.nodes.iter() {
+    match node {
+      forge::Node::Model(m) => {
+        let Some(model) = db.lookup_model(forge::model_path(&m.path)) else { continue };
+        let range = source_map.models[&id].to_node(&json).text_range();
+        refs.push((FileRange { file: model, range: None }, FileRange { file, range: Some(range) }));
+      }
+      forge::Node::TextureDef(def) if !def.value.starts_with('#') => {
+        let Ok(path) = def.value.parse() else { continue };
+        let Some(texture) = db.lookup_texture(TexturePath::new(path)) else { continue };
+        let Some(value) = source_map.texture_defs[&id].tree(&json).value() else { continue };
+        let range = value.syntax().text_range();
+        refs
+          .push((FileRange { file: texture, range: None }, FileRange { file, range: Some(range) }));
+      }
+      _ => {}
+    }
+  }
+}
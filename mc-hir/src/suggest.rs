@@ -0,0 +1,104 @@
+//! "Did you mean" suggestions for paths that don't resolve.
+
+use mc_source::ResolvedPath;
+use mc_syntax::ast::{self, AstNode};
+
+use crate::{diagnostic::Diagnostics, HirDatabase};
+
+/// Reports a model path that doesn't resolve to any model. `prefix` is added
+/// to the start of the path when it is resolved, like `block` in blockstates
+/// before 1.13.
+pub fn missing_model(
+  db: &dyn HirDatabase,
+  diagnostics: &mut Diagnostics,
+  value: &ast::Value,
+  written: &str,
+  prefix: &[&str],
+) {
+  let range = value.syntax().text_range();
+  let diagnostic = diagnostics.error(range, format!("model `{written}` not found"));
+
+  if let Some(closest) = closest(written, model_candidates(db, written, prefix)) {
+    diagnostic.hint(format!("did you mean `{closest}`?")).suggest_replace(
+      format!("replace with `{closest}`"),
+      range,
+      format!("\"{closest}\""),
+    );
+  }
+}
+
+/// Returns every model in the workspace, written in the same style as
+/// `written`. Models outside of `prefix` are skipped.
+fn model_candidates(db: &dyn HirDatabase, written: &str, prefix: &[&str]) -> Vec<String> {
+  let namespaced = written.contains(':');
+
+  let mut candidates = vec![];
+  for namespace in db.workspace().namespaces.iter() {
+    for file in namespace.files.iter() {
+      let Some(ResolvedPath::Model(mut model)) = file.resolved_path() else { continue };
+      if model.path.segments.len() <= prefix.len()
+        || model.path.segments.iter().zip(prefix).any(|(a, b)| a != b)
+      {
+        continue;
+      }
+      model.path.segments.drain(..prefix.len());
+
+      candidates.push(if namespaced {
+        model.path.to_extended_string()
+      } else {
+        model.path.to_string()
+      });
+    }
+  }
+
+  // The same model may be in multiple layers.
+  candidates.sort();
+  candidates.dedup();
+  candidates
+}
+
+/// Returns the candidate closest to `name`, if any are close enough to be a
+/// typo.
+fn closest(name: &str, candidates: Vec<String>) -> Option<String> {
+  let max = (name.chars().count() / 3).max(1);
+
+  candidates
+    .into_iter()
+    .map(|c| (edit_distance(name, &c), c))
+    .filter(|(distance, _)| *distance <= max)
+    .min_by_key(|(distance, _)| *distance)
+    .map(|(_, c)| c)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b = b.chars().collect::<Vec<_>>();
+  let mut prev = (0..=b.len()).collect::<Vec<_>>();
+  let mut curr = vec![0; b.len() + 1];
+
+  for (i, ca) in a.chars().enumerate() {
+    curr[0] = i + 1;
+    for (j, &cb) in b.iter().enumerate() {
+      let substitute = prev[j] + usize::from(ca != cb);
+      curr[j + 1] = substitute.min(prev[j + 1] + 1).min(curr[j] + 1);
+    }
+    std::mem::swap(&mut prev, &mut curr);
+  }
+
+  prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn closest_candidate() {
+    assert_eq!(edit_distance("cube_al", "cube_all"), 1);
+    assert_eq!(edit_distance("", "abc"), 3);
+
+    let candidates = vec!["block/cube".into(), "block/cube_all".into(), "block/stone".into()];
+    assert_eq!(closest("block/cube_al", candidates.clone()), Some("block/cube_all".into()));
+    assert_eq!(closest("item/apple", candidates), None);
+  }
+}
@@ -0,0 +1,155 @@
+//! An index of every resource in the workspace, for fuzzy searching.
+
+use std::{collections::HashMap, sync::Arc};
+
+use mc_source::{File, FileId, FileType, ResolvedPath};
+
+use crate::HirDatabase;
+
+/// A resource that can be searched for, like a model or a texture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+  /// The resource location, like `mymod:block/machine_frame`.
+  pub name: String,
+  pub ty:   FileType,
+  pub file: FileId,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SymbolIndex {
+  /// Every symbol, sorted by name.
+  symbols:  Vec<Symbol>,
+  /// The symbols that contain each character. A symbol can only match a query
+  /// if it contains every character in the query, so searches only need to
+  /// look at the symbols for the rarest character.
+  postings: HashMap<char, Vec<u32>>,
+}
+
+impl SymbolIndex {
+  /// Returns the symbols that fuzzy match `query`, best matches first.
+  pub fn search(&self, query: &str, limit: usize) -> Vec<&Symbol> {
+    let query = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase);
+    let query = query.collect::<Vec<_>>();
+    if query.is_empty() {
+      return self.symbols.iter().take(limit).collect();
+    }
+
+    let Some(candidates) = query.iter().map(|c| self.postings.get(c)).collect::<Option<Vec<_>>>()
+    else {
+      return vec![];
+    };
+    let candidates = candidates.into_iter().min_by_key(|p| p.len()).unwrap();
+
+    let mut matches = candidates
+      .iter()
+      .filter_map(|&i| {
+        let symbol = &self.symbols[i as usize];
+        fuzzy_score(&symbol.name, &query).map(|score| (score, symbol))
+      })
+      .collect::<Vec<_>>();
+
+    // Shorter names are closer matches when the score is tied. Candidates are
+    // already sorted by name, and this sort is stable.
+    matches.sort_by(|(a, x), (b, y)| b.cmp(a).then(x.name.len().cmp(&y.name.len())));
+    matches.into_iter().take(limit).map(|(_, s)| s).collect()
+  }
+}
+
+pub fn symbol_index(db: &dyn HirDatabase) -> Arc<SymbolIndex> {
+  let index = db.path_index();
+
+  // Files overridden by a higher layer are never used, so they're left out.
+  let mut symbols = index
+    .used_files()
+    .filter_map(|file| Some(Symbol { name: resource_name(file)?, ty: file.ty, file: file.id }))
+    .collect::<Vec<_>>();
+  symbols.sort_by(|a, b| a.name.cmp(&b.name).then(a.file.cmp(&b.file)));
+
+  let mut postings = HashMap::<char, Vec<u32>>::new();
+  for (i, symbol) in symbols.iter().enumerate() {
+    let mut chars = symbol.name.chars().flat_map(char::to_lowercase).collect::<Vec<_>>();
+    chars.sort();
+    chars.dedup();
+    for c in chars {
+      postings.entry(c).or_default().push(i as u32);
+    }
+  }
+
+  Arc::new(SymbolIndex { symbols, postings })
+}
+
+/// Returns the resource location of a file, which is how other files refer to
+/// it.
+fn resource_name(file: &File) -> Option<String> {
+  let path = match file.resolved_path() {
+    Some(ResolvedPath::Model(model)) => model.path,
+    Some(ResolvedPath::Texture(texture)) => texture.path,
+    None => {
+      let (dir, ext) = match file.ty {
+        FileType::Blockstate => ("blockstates", ".json"),
+        FileType::TextureMeta => ("textures", ".png.mcmeta"),
+        _ => return None,
+      };
+
+      let mut path = file.path.clone();
+      if path.segments.first().is_none_or(|s| s != dir) {
+        return None;
+      }
+      path.segments.remove(0);
+      let last = path.segments.last_mut()?;
+      *last = last.strip_suffix(ext)?.to_string();
+      path
+    }
+  };
+
+  Some(path.to_extended_string())
+}
+
+/// Scores how well `query` matches `name`, or returns `None` if the characters
+/// of `query` don't all appear in order. Matches at the start of a segment,
+/// like `machine` in `block/machine_frame`, and runs of matches score higher.
+fn fuzzy_score(name: &str, query: &[char]) -> Option<u32> {
+  let mut query = query.iter().peekable();
+  let mut score = 0;
+  let mut prev = None;
+  let mut prev_matched = false;
+
+  for c in name.chars().flat_map(char::to_lowercase) {
+    let Some(&&q) = query.peek() else { break };
+
+    if c == q {
+      query.next();
+      score += 1;
+      if prev_matched {
+        score += 2;
+      }
+      if prev.is_none_or(|p| matches!(p, ':' | '/' | '_' | '.')) {
+        score += 3;
+      }
+      prev_matched = true;
+    } else {
+      prev_matched = false;
+    }
+    prev = Some(c);
+  }
+
+  query.peek().is_none().then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fuzzy_scores() {
+    let score = |name: &str, q: &str| fuzzy_score(name, &q.chars().collect::<Vec<_>>());
+
+    assert!(score("test:block/machine_frame", "mafr").is_some());
+    assert_eq!(score("test:block/machine_frame", "frma"), None);
+
+    // Matching the start of each word is better than matching scattered
+    // letters, and runs of letters are better than gaps.
+    assert!(score("test:block/machine_frame", "mf") > score("test:block/campfire", "mf"));
+    assert!(score("test:block/frame", "frame") > score("test:block/furnace_me", "frame"));
+  }
+}
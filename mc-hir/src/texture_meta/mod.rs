@@ -0,0 +1,98 @@
+//! Texture metadata, from `textures/**/*.png.mcmeta` files. These control
+//! animation and filtering of the texture they sit next to.
+
+use std::sync::Arc;
+
+mod parse;
+mod validate;
+
+use mc_source::{FileId, Path};
+use mc_syntax::{ast, AstPtr};
+
+use crate::{diagnostic::Diagnostics, HirDatabase};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TextureMeta {
+  pub animation: Option<Animation>,
+  pub texture:   Option<TextureProps>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TextureMetaSourceMap {
+  pub animation: Option<AstPtr<ast::Value>>,
+  pub width:     Option<AstPtr<ast::Value>>,
+  pub height:    Option<AstPtr<ast::Value>>,
+
+  /// The source of each frame in `Animation::frames`.
+  pub frames: Vec<AstPtr<ast::Value>>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Animation {
+  pub frametime:   Option<u32>,
+  pub interpolate: Option<bool>,
+  pub width:       Option<u32>,
+  pub height:      Option<u32>,
+  pub frames:      Option<Vec<Frame>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Frame {
+  pub index: u32,
+  pub time:  Option<u32>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TextureProps {
+  pub blur:  Option<bool>,
+  pub clamp: Option<bool>,
+}
+
+impl Animation {
+  /// Returns the size of a single frame, given the size of the whole texture.
+  /// This matches vanilla: if the frame size isn't set, frames are square.
+  pub fn frame_size(&self, width: u32, height: u32) -> (u32, u32) {
+    match (self.width, self.height) {
+      (Some(w), Some(h)) => (w, h),
+      (Some(w), None) => (w, height),
+      (None, Some(h)) => (width, h),
+      (None, None) => (width.min(height), width.min(height)),
+    }
+  }
+}
+
+/// Returns the path of the texture that a `.png.mcmeta` file describes.
+pub fn texture_path(meta_path: &Path) -> Option<Path> {
+  let mut path = meta_path.clone();
+  let last = path.segments.last_mut()?;
+  *last = last.strip_suffix(".mcmeta")?.to_owned();
+  Some(path)
+}
+
+pub fn parse_texture_meta(
+  db: &dyn HirDatabase,
+  file_id: FileId,
+) -> (Arc<TextureMeta>, Arc<TextureMetaSourceMap>, Arc<Diagnostics>) {
+  let json = db.parse_json(file_id);
+
+  let mut diagnostics = Diagnostics::new();
+  let mut meta = TextureMeta::default();
+  let mut source_map = TextureMetaSourceMap::default();
+
+  let tree = json.tree();
+
+  parse::parse(&mut meta, &mut source_map, &mut diagnostics, &tree);
+
+  (Arc::new(meta), Arc::new(source_map), Arc::new(diagnostics))
+}
+
+pub fn validate_texture_meta(db: &dyn HirDatabase, file_id: FileId) -> Arc<Diagnostics> {
+  let json = db.parse_json(file_id);
+
+  let (_, source_map, diagnostics) = parse_texture_meta(db, file_id);
+  let mut diagnostics = (*diagnostics).clone();
+
+  validate::validate(db, file_id, &source_map, &json, &mut diagnostics);
+
+  Arc::new(diagnostics)
+}
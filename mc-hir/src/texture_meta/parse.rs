@@ -0,0 +1,143 @@
+use crate::{diagnostic::Diagnostics, parse::Parser};
+use mc_syntax::{
+  ast::{self, AstNode},
+  AstPtr, Json,
+};
+
+use super::*;
+
+struct TextureMetaParser<'a> {
+  parser:     Parser<'a>,
+  meta:       &'a mut TextureMeta,
+  source_map: &'a mut TextureMetaSourceMap,
+}
+
+pub fn parse(
+  meta: &mut TextureMeta,
+  source_map: &mut TextureMetaSourceMap,
+  diagnostics: &mut Diagnostics,
+  json: &Json,
+) {
+  let mut parser = TextureMetaParser { parser: Parser::new(json, diagnostics), meta, source_map };
+  parser.parse_root(json);
+}
+
+impl TextureMetaParser<'_> {
+  fn parse_root(&mut self, json: &ast::Json) {
+    let Some(root) = json.value() else { return };
+    let Some(obj) = self.parser.object(root) else { return };
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "animation" => {
+          self.source_map.animation = Some(AstPtr::new(&value));
+          self.meta.animation = self.parse_animation(value);
+        }
+        "texture" => self.meta.texture = self.parse_texture(value),
+        // Used by villager professions and GUI sprites, neither of which we validate.
+        "villager" | "gui" => {}
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+  }
+
+  fn parse_animation(&mut self, a: ast::Value) -> Option<Animation> {
+    let mut animation = Animation::default();
+
+    let obj = self.parser.object(a)?;
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "frametime" => animation.frametime = self.positive(&value),
+        "interpolate" => animation.interpolate = self.parser.bool(&value),
+        "width" => {
+          self.source_map.width = Some(AstPtr::new(&value));
+          animation.width = self.positive(&value);
+        }
+        "height" => {
+          self.source_map.height = Some(AstPtr::new(&value));
+          animation.height = self.positive(&value);
+        }
+        "frames" => {
+          let Some(arr) = self.parser.array(value) else { continue };
+          let mut frames = vec![];
+          for item in arr.values() {
+            let Some(frame) = self.parse_frame(&item) else { continue };
+            self.source_map.frames.push(AstPtr::new(&item));
+            frames.push(frame);
+          }
+          animation.frames = Some(frames);
+        }
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    Some(animation)
+  }
+
+  fn parse_frame(&mut self, f: &ast::Value) -> Option<Frame> {
+    match f {
+      ast::Value::NumberValue(_) => Some(Frame { index: self.index(f)?, time: None }),
+      ast::Value::Object(obj) => {
+        let mut index = None;
+        let mut time = None;
+        for (key, value) in obj.iter() {
+          match key.parse_text().as_str() {
+            "index" => index = self.index(&value),
+            "time" => time = self.positive(&value),
+            _ => self.parser.warn_unknown_key(key),
+          }
+        }
+
+        match index {
+          Some(index) => Some(Frame { index, time }),
+          None => {
+            self.parser.diagnostics.error(f.syntax(), "missing frame `index`");
+            None
+          }
+        }
+      }
+      _ => {
+        self.parser.diagnostics.error(f.syntax(), "expected a frame index or object");
+        None
+      }
+    }
+  }
+
+  fn parse_texture(&mut self, t: ast::Value) -> Option<TextureProps> {
+    let mut texture = TextureProps::default();
+
+    let obj = self.parser.object(t)?;
+    for (key, value) in obj.iter() {
+      match key.parse_text().as_str() {
+        "blur" => texture.blur = self.parser.bool(&value),
+        "clamp" => texture.clamp = self.parser.bool(&value),
+        // Only used before 1.13, and doesn't do anything.
+        "mipmaps" => {}
+        _ => self.parser.warn_unknown_key(key),
+      }
+    }
+
+    Some(texture)
+  }
+
+  fn positive(&mut self, value: &ast::Value) -> Option<u32> {
+    let n = self.parser.int(value)?;
+    match u32::try_from(n) {
+      Ok(n) if n > 0 => Some(n),
+      _ => {
+        self.parser.diagnostics.error(value.syntax(), "expected a positive integer");
+        None
+      }
+    }
+  }
+
+  fn index(&mut self, value: &ast::Value) -> Option<u32> {
+    let n = self.parser.int(value)?;
+    match u32::try_from(n) {
+      Ok(n) => Some(n),
+      Err(_) => {
+        self.parser.diagnostics.error(value.syntax(), "frame index cannot be negative");
+        None
+      }
+    }
+  }
+}
@@ -0,0 +1,113 @@
+use mc_source::{FileId, PngHeader, TextRange};
+use mc_syntax::{ast, AstPtr, Json, Parse};
+
+use crate::{
+  diagnostic::{first_char, Diagnostics},
+  HirDatabase,
+};
+
+use super::{texture_path, Animation, TextureMetaSourceMap};
+
+struct Validator<'a> {
+  source_map:  &'a TextureMetaSourceMap,
+  json:        &'a Parse<Json>,
+  diagnostics: &'a mut Diagnostics,
+}
+
+pub fn validate(
+  db: &dyn HirDatabase,
+  file_id: FileId,
+  source_map: &TextureMetaSourceMap,
+  json: &Parse<Json>,
+  diagnostics: &mut Diagnostics,
+) {
+  let meta = db.parse_texture_meta(file_id);
+  let mut validator = Validator { source_map, json, diagnostics };
+
+  let workspace = db.workspace();
+  let Some((_, file)) = workspace.file(file_id) else { return };
+  let Some(path) = texture_path(&file.path) else { return };
+
+  let Some(&texture) = db.lookup_path(path.clone()).first() else {
+    validator.diagnostics.warn(
+      TextRange::new(0.into(), 1.into()),
+      format!("texture `{}` not found", path.to_extended_string()),
+    );
+    return;
+  };
+
+  // If the PNG is broken, there isn't much we can check.
+  let Some(header) = db.png_header(texture) else { return };
+
+  if let Some(ref animation) = meta.animation {
+    validator.validate_animation(animation, header);
+  }
+}
+
+impl Validator<'_> {
+  fn validate_animation(&mut self, animation: &Animation, header: PngHeader) {
+    let (frame_width, frame_height) = animation.frame_size(header.width, header.height);
+
+    let mut size_ok = true;
+    if !header.width.is_multiple_of(frame_width) {
+      let span = self.size_span(self.source_map.width.as_ref());
+      self
+        .diagnostics
+        .error(
+          span,
+          format!(
+            "texture width {} is not a multiple of the frame width {}",
+            header.width, frame_width
+          ),
+        )
+        .hint(format!("the texture is {}x{}", header.width, header.height));
+      size_ok = false;
+    }
+    if !header.height.is_multiple_of(frame_height) {
+      let span = self.size_span(self.source_map.height.as_ref());
+      self
+        .diagnostics
+        .error(
+          span,
+          format!(
+            "texture height {} is not a multiple of the frame height {}",
+            header.height, frame_height
+          ),
+        )
+        .hint(format!("the texture is {}x{}", header.width, header.height));
+      size_ok = false;
+    }
+
+    // The frame count is meaningless if the frames don't fit.
+    if !size_ok {
+      return;
+    }
+
+    let frame_count = (header.width / frame_width) * (header.height / frame_height);
+    let Some(ref frames) = animation.frames else { return };
+    for (frame, ptr) in frames.iter().zip(self.source_map.frames.iter()) {
+      if frame.index >= frame_count {
+        self
+          .diagnostics
+          .error(ptr.to_node(self.json), format!("frame index {} is out of bounds", frame.index))
+          .hint(format!(
+            "the texture has {} frames of {}x{}",
+            frame_count, frame_width, frame_height
+          ));
+      }
+    }
+  }
+
+  /// Returns the span of the `width` or `height` key if its set, or the start
+  /// of the `animation` object otherwise.
+  fn size_span(&self, ptr: Option<&AstPtr<ast::Value>>) -> TextRange {
+    if let Some(ptr) = ptr {
+      return ptr.to_node(self.json).text_range();
+    }
+
+    match self.source_map.animation {
+      Some(ref animation) => first_char(animation.to_node(self.json).text_range()),
+      None => first_char(TextRange::default()),
+    }
+  }
+}
@@ -0,0 +1,257 @@
+//! Project configuration. This is read from `mclsp.toml` at the workspace root,
+//! and then overridden by the client's `initializationOptions`.
+
+use std::path::{Path, PathBuf};
+
+use mc_source::Layer;
+use serde::Deserialize;
+
+pub const CONFIG_FILE: &str = "mclsp.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+  /// The workspace root. All relative paths are resolved against this.
+  pub root: PathBuf,
+
+  /// The minecraft version, if set explicitly.
+  pub minecraft_version: Option<String>,
+
+  /// Directories containing asset namespaces (ie, `src/main/resources/assets`)
+  /// that are part of the project.
+  pub assets: Vec<PathBuf>,
+
+  /// Extra asset directories, that are indexed but never edited.
+  pub read_only_assets: Vec<PathBuf>,
+
+  /// Mod dependencies, whose jars are indexed. If this isn't set, the
+  /// dependencies are detected from the build scripts.
+  pub dependencies: Option<Vec<DependencySource>>,
+}
+
+/// A mod dependency, either as a maven coordinate to look up in the gradle
+/// cache, or as a path to a jar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencySource {
+  Maven(mc_gradle::Dependency),
+  Jar(PathBuf),
+}
+
+/// The raw config, as written by the user. Every field is optional, so that
+/// `initializationOptions` only overrides the fields it sets.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+  minecraft_version: Option<String>,
+  assets:            Option<Vec<PathBuf>>,
+  read_only_assets:  Option<Vec<PathBuf>>,
+  dependencies:      Option<Vec<String>>,
+}
+
+impl Config {
+  /// Loads the config for the given workspace root. Errors in the config file
+  /// are logged, and the file is ignored.
+  pub fn load(root: PathBuf, init_options: Option<serde_json::Value>) -> Config {
+    let mut file = ConfigFile::default();
+
+    let path = root.join(CONFIG_FILE);
+    match std::fs::read_to_string(&path) {
+      Ok(text) => match toml::from_str::<ConfigFile>(&text) {
+        Ok(f) => file = f,
+        Err(e) => error!("failed to parse {}: {}", path.display(), e),
+      },
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+      Err(e) => error!("failed to read {}: {}", path.display(), e),
+    }
+
+    if let Some(options) = init_options {
+      match serde_json::from_value::<ConfigFile>(options) {
+        Ok(options) => file.merge(options),
+        Err(e) => error!("failed to parse initializationOptions: {}", e),
+      }
+    }
+
+    Config::from_file(root, file)
+  }
+
+  fn from_file(root: PathBuf, file: ConfigFile) -> Config {
+    let assets = match file.assets {
+      Some(assets) => assets.into_iter().map(|p| root.join(p)).collect(),
+      None => detect_assets(&root),
+    };
+    let read_only_assets =
+      file.read_only_assets.unwrap_or_default().into_iter().map(|p| root.join(p)).collect();
+
+    let dependencies = file.dependencies.map(|deps| {
+      deps
+        .into_iter()
+        .map(|dep| match mc_gradle::Dependency::parse(&dep) {
+          Some(dep) => DependencySource::Maven(dep),
+          None => DependencySource::Jar(root.join(dep)),
+        })
+        .collect()
+    });
+
+    Config {
+      minecraft_version: file.minecraft_version,
+      assets,
+      read_only_assets,
+      dependencies,
+      root,
+    }
+  }
+
+  /// Returns all the asset directories and archives from the config, along
+  /// with the layer they belong to.
+  pub fn asset_roots(&self) -> impl Iterator<Item = (&Path, Layer)> {
+    let project = self.assets.iter().map(|p| (p.as_path(), Layer::Project));
+    let read_only = self.read_only_assets.iter().map(|p| (p.as_path(), Layer::Dependency));
+    project.chain(read_only)
+  }
+
+  /// Returns the path to the `pack.mcmeta` next to each of the project's asset
+  /// directories. These files may not exist.
+  pub fn pack_files(&self) -> impl Iterator<Item = PathBuf> + '_ {
+    self.assets.iter().filter_map(|p| Some(p.parent()?.join("pack.mcmeta")))
+  }
+
+  /// Returns the jars of all the dependencies, either from the config or from
+  /// the build scripts. Missing jars are logged and skipped.
+  pub fn dependency_jars(&self) -> Vec<PathBuf> {
+    let sources = match self.dependencies {
+      Some(ref deps) => deps.clone(),
+      None => mc_gradle::detect_dependencies(&self.root)
+        .into_iter()
+        .map(DependencySource::Maven)
+        .collect(),
+    };
+
+    sources
+      .into_iter()
+      .filter_map(|source| match source {
+        DependencySource::Maven(dep) => match mc_gradle::find_dependency_jar(&dep) {
+          Ok(path) => Some(path),
+          Err(e) => {
+            warn!("failed to find jar for dependency {}:{}: {}", dep.group, dep.name, e);
+            None
+          }
+        },
+        DependencySource::Jar(path) => Some(path),
+      })
+      .collect()
+  }
+}
+
+impl ConfigFile {
+  fn merge(&mut self, other: ConfigFile) {
+    if other.minecraft_version.is_some() {
+      self.minecraft_version = other.minecraft_version;
+    }
+    if other.assets.is_some() {
+      self.assets = other.assets;
+    }
+    if other.read_only_assets.is_some() {
+      self.read_only_assets = other.read_only_assets;
+    }
+    if other.dependencies.is_some() {
+      self.dependencies = other.dependencies;
+    }
+  }
+}
+
+/// Finds all the `src/main/resources/assets` directories in the root project,
+/// and in any direct subprojects (like `common`, `forge`, and `fabric` in a
+/// multi-loader setup).
+fn detect_assets(root: &Path) -> Vec<PathBuf> {
+  let assets_in = |dir: &Path| dir.join("src").join("main").join("resources").join("assets");
+
+  let mut found = vec![];
+
+  if assets_in(root).is_dir() {
+    found.push(assets_in(root));
+  }
+
+  if let Ok(dir) = std::fs::read_dir(root) {
+    let mut subprojects = dir.filter_map(|e| e.ok()).map(|e| e.path()).collect::<Vec<_>>();
+    subprojects.sort();
+
+    for path in subprojects {
+      if assets_in(&path).is_dir() {
+        found.push(assets_in(&path));
+      }
+    }
+  }
+
+  found
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn init_options_override_file() {
+    let mut file: ConfigFile = toml::from_str(
+      r#"
+      minecraft_version = "1.12.2"
+      assets = ["forge/src/main/resources/assets"]
+      "#,
+    )
+    .unwrap();
+
+    file
+      .merge(serde_json::from_value(serde_json::json!({ "minecraft_version": "1.20.1" })).unwrap());
+
+    let config = Config::from_file(PathBuf::from("/foo"), file);
+
+    assert_eq!(
+      config,
+      Config {
+        root:              PathBuf::from("/foo"),
+        minecraft_version: Some("1.20.1".into()),
+        assets:            vec![PathBuf::from("/foo/forge/src/main/resources/assets")],
+        read_only_assets:  vec![],
+        dependencies:      None,
+      }
+    );
+  }
+
+  #[test]
+  fn parses_dependencies() {
+    let file: ConfigFile =
+      toml::from_str(r#"dependencies = ["mezz.jei:jei_1.12.2:4.16.1.302", "libs/core.jar"]"#)
+        .unwrap();
+
+    let config = Config::from_file(PathBuf::from("/foo"), file);
+
+    assert_eq!(
+      config.dependencies,
+      Some(vec![
+        DependencySource::Maven(
+          mc_gradle::Dependency::parse("mezz.jei:jei_1.12.2:4.16.1.302").unwrap()
+        ),
+        DependencySource::Jar(PathBuf::from("/foo/libs/core.jar")),
+      ])
+    );
+  }
+
+  #[test]
+  fn detects_subprojects() {
+    let root = std::env::temp_dir().join("mclsp-config-detects-subprojects");
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("common/src/main/resources/assets")).unwrap();
+    std::fs::create_dir_all(root.join("fabric/src/main/resources/assets")).unwrap();
+    std::fs::create_dir_all(root.join("gradle")).unwrap();
+
+    let config = Config::from_file(root.clone(), ConfigFile::default());
+
+    assert_eq!(
+      config.assets,
+      vec![
+        root.join("common/src/main/resources/assets"),
+        root.join("fabric/src/main/resources/assets"),
+      ]
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+  }
+}
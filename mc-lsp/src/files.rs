@@ -2,14 +2,26 @@
 
 use std::{
   collections::HashMap,
+  io::{self, Read},
   path::{Path, PathBuf},
+  sync::{Arc, OnceLock},
 };
 
-use mc_source::FileId;
+use lsp_types::Url;
+use mc_source::{FileId, FileLoader};
+use parking_lot::{Mutex, RwLock};
+
+/// The URI scheme used for files that don't exist on disk, like files within a
+/// jar. These URIs look like `mclsp:/path/to/client.jar!/assets/foo.json`.
+pub const VIRTUAL_SCHEME: &str = "mclsp";
 
 pub struct Files {
-  /// Namespaces to absolute paths.
-  namespace_roots: HashMap<Namespace, PathBuf>,
+  /// Absolute paths to namespace roots. There may be multiple roots for the
+  /// same namespace, for example when a resource pack overrides vanilla files.
+  namespace_roots: Vec<PathBuf>,
+
+  /// Read-only zip archives, which files may be read from.
+  archives: Vec<Archive>,
 
   files:       HashMap<FileId, File>,
   file_lookup: HashMap<FilePath, FileId>,
@@ -17,11 +29,23 @@ pub struct Files {
   changes: Vec<FileId>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Namespace(String);
+/// Reads textures for the analysis, the first time they're needed.
+pub struct TextureLoader(pub Arc<RwLock<Files>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct RootId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArchiveId(u32);
+
+struct Archive {
+  path: PathBuf,
+  zip:  Mutex<zip::ZipArchive<std::fs::File>>,
+}
 
 struct File {
-  content: FileContent,
+  /// Files within archives are only read once something needs them.
+  content: OnceLock<FileContent>,
   path:    FilePath,
 }
 
@@ -33,30 +57,44 @@ pub enum FileContent {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum FilePath {
-  Rooted { namespace: Namespace, relative_path: PathBuf },
+  Rooted { root: RootId, relative_path: PathBuf },
   // Some files don't have a source root, in which case we just leave this blank.
   Absolute(PathBuf),
+  Archived { archive: ArchiveId, entry: String },
+}
+
+impl FileLoader for TextureLoader {
+  fn load_bytes(&self, file_id: FileId) -> Arc<[u8]> {
+    match self.0.read().read(file_id) {
+      FileContent::Png(bytes) => bytes.into(),
+      FileContent::Json(_) => Arc::from([]),
+    }
+  }
 }
 
 impl Files {
   pub fn new() -> Self {
     Files {
-      namespace_roots: HashMap::new(),
+      namespace_roots: vec![],
+      archives:        vec![],
       files:           HashMap::new(),
       file_lookup:     HashMap::new(),
       changes:         vec![],
     }
   }
 
-  pub fn add_namespace(&mut self, name: String, namespace_path: PathBuf) {
-    let namespace = Namespace(name);
-    self.namespace_roots.insert(namespace.clone(), namespace_path.clone());
+  pub fn add_namespace(&mut self, namespace_path: PathBuf) {
+    if self.namespace_roots.contains(&namespace_path) {
+      return;
+    }
+
+    let root = RootId(self.namespace_roots.len() as u32);
+    self.namespace_roots.push(namespace_path.clone());
 
     for file in self.files.values_mut() {
       if let FilePath::Absolute(path) = &mut file.path {
         if let Ok(relative) = path.strip_prefix(&namespace_path) {
-          file.path =
-            FilePath::Rooted { namespace: namespace.clone(), relative_path: relative.into() };
+          file.path = FilePath::Rooted { root, relative_path: relative.into() };
         }
       }
     }
@@ -65,10 +103,10 @@ impl Files {
   fn make_file_path(&self, path: &Path) -> FilePath {
     assert!(path.is_absolute(), "cannot create source root for relative path {}", path.display());
 
-    for (namespace, root) in &self.namespace_roots {
+    for (i, root) in self.namespace_roots.iter().enumerate() {
       if let Ok(relative) = path.strip_prefix(root) {
         return FilePath::Rooted {
-          namespace:     namespace.clone(),
+          root:          RootId(i as u32),
           relative_path: relative.to_path_buf(),
         };
       }
@@ -77,15 +115,54 @@ impl Files {
     FilePath::Absolute(path.to_path_buf())
   }
 
+  /// Opens the given zip archive, so that files can be read from it.
+  pub fn mount_archive(&mut self, path: &Path) -> io::Result<ArchiveId> {
+    let zip = zip::ZipArchive::new(std::fs::File::open(path)?)
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let id = ArchiveId(self.archives.len() as u32);
+    self.archives.push(Archive { path: path.to_path_buf(), zip: Mutex::new(zip) });
+    Ok(id)
+  }
+
+  /// Returns the names of all the files in the given archive.
+  pub fn archive_entries(&self, archive: ArchiveId) -> Vec<String> {
+    let zip = self.archives[archive.0 as usize].zip.lock();
+    zip.file_names().filter(|name| !name.ends_with('/')).map(|name| name.to_string()).collect()
+  }
+
   pub fn read(&self, id: FileId) -> FileContent {
     let file = self.files.get(&id).unwrap();
-    file.content.clone()
+    file.content.get_or_init(|| self.load(&file.path)).clone()
   }
   pub fn write(&mut self, id: FileId, content: FileContent) {
-    self.files.get_mut(&id).unwrap().content = content;
+    self.files.get_mut(&id).unwrap().content = OnceLock::from(content);
     self.changes.push(id);
   }
 
+  fn load(&self, path: &FilePath) -> FileContent {
+    let FilePath::Archived { archive, entry } = path else {
+      // Files on disk are always written when they're created.
+      return FileContent::Json(String::new());
+    };
+
+    let archive = &self.archives[archive.0 as usize];
+    let mut zip = archive.zip.lock();
+
+    let mut bytes = vec![];
+    if let Err(e) =
+      zip.by_name(entry).map_err(io::Error::other).and_then(|mut f| f.read_to_end(&mut bytes))
+    {
+      error!("failed to read {} from {}: {}", entry, archive.path.display(), e);
+    }
+
+    if entry.ends_with(".png") {
+      FileContent::Png(bytes)
+    } else {
+      FileContent::Json(String::from_utf8_lossy(&bytes).into_owned())
+    }
+  }
+
   pub fn take_changes(&mut self) -> Vec<FileId> { self.changes.drain(..).collect() }
 
   #[track_caller]
@@ -94,7 +171,19 @@ impl Files {
     let id = FileId::new_raw(self.files.len() as u32);
 
     self.file_lookup.insert(path.clone(), id);
-    self.files.insert(id, File { content: FileContent::Json(String::new()), path });
+    self.files.insert(id, File { content: OnceLock::from(FileContent::Json(String::new())), path });
+
+    id
+  }
+
+  /// Creates a file for an entry within an archive. Its content will be read
+  /// the first time it is needed.
+  pub fn create_archived(&mut self, archive: ArchiveId, entry: &str) -> FileId {
+    let path = FilePath::Archived { archive, entry: entry.to_string() };
+    let id = FileId::new_raw(self.files.len() as u32);
+
+    self.file_lookup.insert(path.clone(), id);
+    self.files.insert(id, File { content: OnceLock::new(), path });
 
     id
   }
@@ -106,21 +195,91 @@ impl Files {
     self.file_lookup.get(&self.make_file_path(path)).copied()
   }
 
-  /// Returns `true` if the given file is in a namespace.
-  pub fn in_namespace(&self, id: FileId) -> bool {
-    match self.files[&id].path {
-      FilePath::Rooted { .. } => true,
-      FilePath::Absolute(_) => false,
+  pub fn id_to_uri(&self, id: FileId) -> Url {
+    let file = self.files.get(&id).unwrap();
+    match &file.path {
+      FilePath::Rooted { root, relative_path } => {
+        Url::from_file_path(self.namespace_roots[root.0 as usize].join(relative_path)).unwrap()
+      }
+      FilePath::Absolute(path) => Url::from_file_path(path).unwrap(),
+      FilePath::Archived { archive, entry } => {
+        let archive = &self.archives[archive.0 as usize];
+        let mut uri = Url::from_file_path(&archive.path).unwrap();
+        uri.set_path(&format!("{}!/{}", uri.path(), entry));
+        // `Url` won't let us change the scheme from a special scheme like `file`.
+        Url::parse(&format!("{VIRTUAL_SCHEME}:{}", uri.path())).unwrap()
+      }
     }
   }
 
-  pub fn id_to_absolute_path(&self, id: FileId) -> PathBuf {
-    let file = self.files.get(&id).unwrap();
-    match &file.path {
-      FilePath::Rooted { namespace, relative_path } => {
-        self.namespace_roots[&namespace].join(relative_path)
+  /// Returns every file on disk at or within `path`, along with its absolute
+  /// path.
+  pub fn files_within(&self, path: &Path) -> Vec<(FileId, PathBuf)> {
+    // Only namespaces that overlap `path` can contain it, so skip the others
+    // without building their paths.
+    let overlapping = self
+      .namespace_roots
+      .iter()
+      .map(|root| root.starts_with(path) || path.starts_with(root))
+      .collect::<Vec<_>>();
+
+    self
+      .files
+      .iter()
+      .filter_map(|(&id, file)| {
+        let absolute = match &file.path {
+          FilePath::Rooted { root, relative_path } => {
+            if !overlapping[root.0 as usize] {
+              return None;
+            }
+            self.namespace_roots[root.0 as usize].join(relative_path)
+          }
+          FilePath::Absolute(path) => path.clone(),
+          FilePath::Archived { .. } => return None,
+        };
+        absolute.starts_with(path).then_some((id, absolute))
+      })
+      .collect()
+  }
+
+  /// Returns the resource path of the file at the absolute path `path`. This
+  /// works for files that don't exist yet, as long as they're in the same
+  /// asset directory as an existing namespace.
+  pub fn resource_path(&self, path: &Path) -> Option<mc_source::Path> {
+    self.namespace_roots.iter().find_map(|root| {
+      let mut components = path.strip_prefix(root.parent()?).ok()?.iter();
+
+      let mut relative =
+        mc_source::Path::new_namespace(components.next()?.to_string_lossy().into_owned());
+      relative.segments = components.map(|s| s.to_string_lossy().into_owned()).collect();
+      Some(relative)
+    })
+  }
+
+  /// Returns the URI that `id` would have if it were moved to the resource
+  /// path `path`, within the same asset directory.
+  pub fn moved_uri(&self, id: FileId, path: &mc_source::Path) -> Option<Url> {
+    let FilePath::Rooted { root, .. } = &self.files.get(&id)?.path else { return None };
+
+    let mut absolute = self.namespace_roots[root.0 as usize].parent()?.join(&path.namespace);
+    absolute.extend(&path.segments);
+    Url::from_file_path(absolute).ok()
+  }
+
+  pub fn uri_to_id(&self, uri: &Url) -> Option<FileId> {
+    match uri.scheme() {
+      "file" => self.get_absolute(&uri.to_file_path().ok()?),
+      VIRTUAL_SCHEME => {
+        // Decode the path by reinterpreting it as a file URI.
+        let path = Url::parse(&format!("file://{}", uri.path())).ok()?.to_file_path().ok()?;
+        let (archive_path, entry) = path.to_str()?.split_once("!/")?;
+
+        let archive = self.archives.iter().position(|a| a.path == Path::new(archive_path))?;
+        let path =
+          FilePath::Archived { archive: ArchiveId(archive as u32), entry: entry.to_string() };
+        self.file_lookup.get(&path).copied()
       }
-      FilePath::Absolute(path) => path.clone(),
+      _ => None,
     }
   }
 }
@@ -134,7 +293,7 @@ mod tests {
     let mut files = Files::new();
     let file = FileId::new_raw(0);
 
-    files.add_namespace("foo".into(), "/foo".into());
+    files.add_namespace("/foo".into());
     let id = files.create(Path::new("/foo/bar"));
     files.write(id, FileContent::Json("bar".to_string()));
 
@@ -147,7 +306,7 @@ mod tests {
     let mut files = Files::new();
     let file = FileId::new_raw(0);
 
-    files.add_namespace("foo".into(), "/foo".into());
+    files.add_namespace("/foo".into());
     let id = files.create(Path::new("/foo/bar"));
     files.write(id, FileContent::Json("bar".to_string()));
 
@@ -155,6 +314,28 @@ mod tests {
     assert_eq!(id, Some(file));
   }
 
+  #[test]
+  fn resource_paths() {
+    let mut files = Files::new();
+
+    files.add_namespace("/assets/foo".into());
+    let id = files.create(Path::new("/assets/foo/models/block/bar.json"));
+
+    assert_eq!(
+      files.resource_path(Path::new("/assets/baz/models/item/bar.json")),
+      Some("baz:models/item/bar.json".parse().unwrap())
+    );
+    assert_eq!(files.resource_path(Path::new("/other/bar.json")), None);
+    assert_eq!(
+      files.moved_uri(id, &"baz:models/item/bar.json".parse().unwrap()),
+      Some(Url::from_file_path("/assets/baz/models/item/bar.json").unwrap())
+    );
+    assert_eq!(
+      files.files_within(Path::new("/assets/foo/models")),
+      vec![(id, PathBuf::from("/assets/foo/models/block/bar.json"))]
+    );
+  }
+
   #[test]
   fn reindex_works() {
     let mut files = Files::new();
@@ -165,15 +346,37 @@ mod tests {
     assert_eq!(files.files[&file_1].path, FilePath::Absolute(PathBuf::from("/foo/bar")));
     assert_eq!(files.files[&file_2].path, FilePath::Absolute(PathBuf::from("/baz")));
 
-    files.add_namespace("foo".into(), "/foo".into());
+    files.add_namespace("/foo".into());
 
     assert_eq!(
       files.files[&file_1].path,
-      FilePath::Rooted {
-        namespace:     Namespace("foo".into()),
-        relative_path: PathBuf::from("bar"),
-      }
+      FilePath::Rooted { root: RootId(0), relative_path: PathBuf::from("bar") }
     );
     assert_eq!(files.files[&file_2].path, FilePath::Absolute(PathBuf::from("/baz")));
   }
+
+  #[test]
+  fn archive_works() {
+    let path = std::env::temp_dir().join("mclsp-files-archive-works.jar");
+    {
+      let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+      zip.add_directory("assets/", zip::write::SimpleFileOptions::default()).unwrap();
+      zip.start_file("assets/my mod/foo.json", zip::write::SimpleFileOptions::default()).unwrap();
+      std::io::Write::write_all(&mut zip, b"{}").unwrap();
+      zip.finish().unwrap();
+    }
+
+    let mut files = Files::new();
+    let archive = files.mount_archive(&path).unwrap();
+    assert_eq!(files.archive_entries(archive), vec!["assets/my mod/foo.json".to_string()]);
+
+    let id = files.create_archived(archive, "assets/my mod/foo.json");
+    assert_eq!(files.read(id), FileContent::Json("{}".into()));
+
+    let uri = files.id_to_uri(id);
+    assert_eq!(uri.scheme(), VIRTUAL_SCHEME);
+    assert_eq!(files.uri_to_id(&uri), Some(id));
+
+    std::fs::remove_file(&path).unwrap();
+  }
 }
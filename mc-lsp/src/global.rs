@@ -2,21 +2,30 @@
 
 use crossbeam_channel::{Receiver, Select, Sender};
 use lsp_server::ErrorCode;
-use mc_analysis::{Analysis, AnalysisHost};
-use mc_source::{FileId, TextSize, Workspace};
+use mc_analysis::{Analysis, AnalysisHost, Cancelled};
+use mc_source::{FileId, FileType, Layer, TextSize, Workspace};
 use parking_lot::RwLock;
-use std::{collections::HashMap, error::Error, path::PathBuf, sync::Arc};
+use std::{
+  collections::{HashMap, HashSet},
+  error::Error,
+  path::PathBuf,
+  sync::Arc,
+};
 
 use lsp_types::{notification::Notification, Url};
 
 use crate::{
-  files::{FileContent, Files},
-  handler,
+  config::Config,
+  files::{FileContent, Files, TextureLoader},
+  handler, search,
+  watch::{ChangeKind, FileChange},
 };
 
 pub struct GlobalState {
   pub sender: Sender<lsp_server::Message>,
 
+  pub config: Config,
+
   pub files: Arc<RwLock<Files>>,
 
   pub analysis_host: AnalysisHost,
@@ -30,24 +39,37 @@ pub struct GlobalState {
   response_sender:   Sender<lsp_server::Message>,
   response_receiver: Receiver<lsp_server::Message>,
 
+  watch_sender:   Sender<Vec<FileChange>>,
+  watch_receiver: Receiver<Vec<FileChange>>,
+  watcher:        Option<notify::RecommendedWatcher>,
+
+  /// Requests sent to the client that are waiting for a response.
+  req_queue: lsp_server::ReqQueue<(), ResponseHandler>,
+
   pool_sender: Sender<Box<dyn FnOnce() + Send>>,
   #[allow(unused)]
   pool:        Vec<std::thread::JoinHandle<()>>,
 }
 
+/// Called with the client's response to a request sent by the server.
+type ResponseHandler = fn(&mut GlobalState, lsp_server::Response);
+
 pub(crate) struct GlobalStateSnapshot {
   pub analysis: Analysis,
   pub files:    Arc<RwLock<Files>>,
+  /// Sends messages to the client, like `window/showMessage`.
+  pub sender:   Sender<lsp_server::Message>,
 }
 
 #[derive(Debug)]
 enum Event {
   Message(lsp_server::Message),
   Response(lsp_server::Message),
+  Watch(Vec<FileChange>),
 }
 
 impl GlobalState {
-  pub fn new(sender: Sender<lsp_server::Message>) -> Self {
+  pub fn new(sender: Sender<lsp_server::Message>, config: Config) -> Self {
     let (pool_tx, pool_rx) = crossbeam_channel::bounded::<Box<dyn FnOnce() + Send>>(0);
 
     let pool = (0..16)
@@ -62,13 +84,20 @@ impl GlobalState {
       .collect();
 
     let (tx, rx) = crossbeam_channel::bounded(0);
+    let (watch_tx, watch_rx) = crossbeam_channel::unbounded();
+
+    let files = Arc::new(RwLock::new(Files::new()));
+    let mut analysis_host = AnalysisHost::new();
+    analysis_host.set_loader(Arc::new(TextureLoader(files.clone())));
 
     GlobalState {
       sender,
 
-      files: Arc::new(RwLock::new(Files::new())),
+      config,
 
-      analysis_host: AnalysisHost::new(),
+      files,
+
+      analysis_host,
 
       diagnostics: HashMap::new(),
       diagnostic_changes: vec![],
@@ -76,6 +105,12 @@ impl GlobalState {
       response_sender: tx,
       response_receiver: rx,
 
+      watch_sender: watch_tx,
+      watch_receiver: watch_rx,
+      watcher: None,
+
+      req_queue: lsp_server::ReqQueue::default(),
+
       pool_sender: pool_tx,
       pool,
     }
@@ -108,12 +143,14 @@ impl GlobalState {
     let mut sel = Select::new();
     sel.recv(receiver);
     sel.recv(&self.response_receiver);
+    sel.recv(&self.watch_receiver);
 
     let op = sel.select();
 
     match op.index() {
       0 => Some(Event::Message(op.recv(receiver).unwrap())),
       1 => Some(Event::Response(op.recv(&self.response_receiver).unwrap())),
+      2 => Some(Event::Watch(op.recv(&self.watch_receiver).unwrap())),
       _ => None,
     }
   }
@@ -122,10 +159,15 @@ impl GlobalState {
     match e {
       Event::Message(lsp_server::Message::Request(req)) => self.handle_request(req),
       Event::Message(lsp_server::Message::Notification(not)) => self.handle_notification(not),
-      Event::Message(lsp_server::Message::Response(_)) => (),
+      Event::Message(lsp_server::Message::Response(res)) => {
+        if let Some(handler) = self.req_queue.outgoing.complete(res.id.clone()) {
+          handler(self, res);
+        }
+      }
       Event::Response(e) => {
         self.sender.send(e)?;
       }
+      Event::Watch(changes) => self.apply_file_changes(changes),
     }
 
     self.process_changes();
@@ -137,33 +179,148 @@ impl GlobalState {
     self.analysis_host.set_workspace(workspace);
     let workspace = self.analysis_host.workspace();
 
-    // Update all the file contents.
+    // Update all the file contents. Textures are read lazily, so that files in
+    // archives aren't decompressed until something needs them.
     {
       let files = self.files.read();
       for namespace in &workspace.namespaces {
         for file in &namespace.files {
-          if let FileContent::Json(text) = files.read(file.id) {
-            self.analysis_host.add_file(file.id, file.ty, text);
+          if file.ty.is_json() {
+            if let FileContent::Json(text) = files.read(file.id) {
+              self.analysis_host.add_file(file.id, file.ty, text);
+            }
+          } else {
+            self.analysis_host.set_texture(file.id);
+          }
+        }
+      }
+
+      for &pack in &workspace.packs {
+        if let FileContent::Json(text) = files.read(pack) {
+          self.analysis_host.add_file(pack, FileType::PackMeta, text);
+        }
+      }
+    }
+  }
+
+  /// Sends a request to the client. `handler` is called with the response.
+  fn send_request<R: lsp_types::request::Request>(
+    &mut self,
+    params: R::Params,
+    handler: ResponseHandler,
+  ) {
+    let req = self.req_queue.outgoing.register(R::METHOD.to_string(), params, handler);
+    self.sender.send(lsp_server::Message::Request(req)).unwrap();
+  }
+
+  /// Asks the client to watch the asset directories for us. If the client
+  /// refuses, we watch them ourselves.
+  pub fn watch_with_client(&mut self) {
+    let registration = crate::watch::client_watcher_registration(&self.config);
+    self.send_request::<lsp_types::request::RegisterCapability>(registration, |global, res| {
+      if let Some(e) = res.error {
+        warn!("client failed to watch files, watching locally instead: {}", e.message);
+        global.watch_locally();
+      }
+    });
+  }
+
+  /// Watches the asset directories ourselves, for clients that can't watch
+  /// files for us.
+  pub fn watch_locally(&mut self) {
+    match crate::watch::spawn_local_watcher(&self.config, self.watch_sender.clone()) {
+      Ok(watcher) => self.watcher = Some(watcher),
+      Err(e) => error!("failed to watch asset directories: {}", e),
+    }
+  }
+
+  /// Adds, re-reads, and removes files from the workspace. Diagnostics are
+  /// refreshed for all the project files, as any of them might depend on the
+  /// changed files.
+  pub fn apply_file_changes(&mut self, changes: Vec<FileChange>) {
+    let mut workspace = (*self.analysis_host.workspace()).clone();
+    let mut added = vec![];
+    let mut removed = vec![];
+
+    {
+      let mut files = self.files.write();
+      for change in changes {
+        match change.kind {
+          ChangeKind::Created | ChangeKind::Changed => {
+            added.extend(search::add_path(&self.config, &mut workspace, &mut files, &change.path));
+          }
+          ChangeKind::Deleted => {
+            removed.extend(search::remove_path(&mut workspace, &files, &change.path));
           }
         }
       }
     }
+
+    // The analysis may need to read textures while we update it, so this must
+    // not hold the write lock.
+    {
+      let files = self.files.read();
+      for &(id, ty) in &added {
+        match files.read(id) {
+          FileContent::Json(text) => self.analysis_host.add_file(id, ty, text),
+          FileContent::Png(_) => self.analysis_host.set_texture(id),
+        }
+      }
+    }
+
+    if added.is_empty() && removed.is_empty() {
+      return;
+    }
+
+    // Refresh diagnostics for every project file. Removed files get their
+    // diagnostics cleared.
+    self.diagnostic_changes.extend(removed);
+    self.diagnostic_changes.extend(workspace.packs.iter().copied());
+    for namespace in workspace.namespaces.iter().filter(|n| n.layer == Layer::Project) {
+      for file in &namespace.files {
+        if file.ty.is_json() {
+          self.diagnostic_changes.push(file.id);
+        }
+      }
+    }
+
+    self.analysis_host.set_workspace(workspace);
   }
 
   fn process_changes(&mut self) {
-    let mut files = self.files.write();
-    let changes = files.take_changes();
+    let changes = self.files.write().take_changes();
+    let files = self.files.read();
 
     for &file in &changes {
-      if let FileContent::Json(text) = files.read(file) {
-        self.analysis_host.change(mc_analysis::Change { file, text });
+      match files.read(file) {
+        FileContent::Json(text) => self.analysis_host.change(mc_analysis::Change { file, text }),
+        FileContent::Png(_) => self.analysis_host.set_texture(file),
       }
     }
 
     let snap = self.analysis_host.snapshot();
+    let workspace = self.analysis_host.workspace();
 
-    for file_id in changes.iter().copied().chain(self.diagnostic_changes.drain(..)) {
-      if !files.in_namespace(file_id) {
+    let mut file_ids = changes;
+    file_ids.append(&mut self.diagnostic_changes);
+    let mut seen = HashSet::new();
+    file_ids.retain(|id| seen.insert(*id));
+
+    for file_id in file_ids {
+      // This file has been deleted, so clear out its diagnostics.
+      if !workspace.contains(file_id) {
+        self
+          .sender
+          .send(lsp_server::Message::Notification(lsp_server::Notification {
+            method: lsp_types::notification::PublishDiagnostics::METHOD.into(),
+            params: serde_json::to_value(lsp_types::PublishDiagnosticsParams {
+              uri:         files.id_to_uri(file_id),
+              diagnostics: vec![],
+              version:     None,
+            })
+            .unwrap(),
+          }))
+          .unwrap();
         continue;
       }
       match files.read(file_id) {
@@ -179,7 +336,7 @@ impl GlobalState {
         .send(lsp_server::Message::Notification(lsp_server::Notification {
           method: lsp_types::notification::PublishDiagnostics::METHOD.into(),
           params: serde_json::to_value(lsp_types::PublishDiagnosticsParams {
-            uri:         Url::from_file_path(files.id_to_absolute_path(file_id)).unwrap(),
+            uri:         files.id_to_uri(file_id),
             diagnostics: diagnostics
               .iter()
               .filter_map(|d| {
@@ -244,11 +401,18 @@ impl GlobalState {
       .on::<lsp_request::SemanticTokensFullRequest>(request::handle_semantic_tokens_full)
       .on::<lsp_request::GotoDefinition>(request::handle_goto_definition)
       .on::<lsp_request::DocumentHighlightRequest>(request::handle_document_highlight)
+      .on::<lsp_request::References>(request::handle_references)
+      .on::<lsp_request::PrepareRenameRequest>(request::handle_prepare_rename)
+      .on::<lsp_request::Rename>(request::handle_rename)
+      .on::<lsp_request::WillRenameFiles>(request::handle_will_rename_files)
+      .on::<lsp_request::DocumentSymbolRequest>(request::handle_document_symbol)
+      .on::<lsp_request::WorkspaceSymbolRequest>(request::handle_workspace_symbol)
       .on::<lsp_request::HoverRequest>(request::handle_hover)
       .on::<lsp_request::Completion>(request::handle_completion)
       .on::<lsp_request::CodeActionRequest>(request::handle_code_action)
       // Custom messages
-      .on::<handler::CanonicalModel>(request::handle_canonical_model);
+      .on::<handler::CanonicalModel>(request::handle_canonical_model)
+      .on::<handler::ReadVirtualFile>(request::handle_read_virtual_file);
   }
 
   fn handle_notification(&mut self, not: lsp_server::Notification) {
@@ -260,7 +424,10 @@ impl GlobalState {
     dispatcher
       .on_sync::<lsp_notification::DidOpenTextDocument>(notification::handle_open_text_document)
       .on_sync::<lsp_notification::DidChangeTextDocument>(notification::handle_change_text_document)
-      .on_sync::<lsp_notification::DidSaveTextDocument>(notification::handle_save_text_document);
+      .on_sync::<lsp_notification::DidSaveTextDocument>(notification::handle_save_text_document)
+      .on_sync::<lsp_notification::DidChangeWatchedFiles>(
+        notification::handle_change_watched_files,
+      );
   }
 
   pub fn absolute_path(&self, uri: &Url) -> Option<PathBuf> {
@@ -272,17 +439,11 @@ impl GlobalState {
   }
 
   pub fn snapshot(&self) -> GlobalStateSnapshot {
-    GlobalStateSnapshot { analysis: self.analysis_host.snapshot(), files: self.files.clone() }
-  }
-}
-
-impl GlobalStateSnapshot {
-  pub fn absolute_path(&self, uri: &Url) -> Option<PathBuf> {
-    if uri.scheme() != "file" {
-      return None;
+    GlobalStateSnapshot {
+      analysis: self.analysis_host.snapshot(),
+      files:    self.files.clone(),
+      sender:   self.sender.clone(),
     }
-
-    uri.to_file_path().ok()
   }
 }
 
@@ -373,7 +534,7 @@ impl RequestDispatcher<'_> {
             error: None,
           }))
           .unwrap(),
-        Err(_) => responder
+        Err(e) if e.is::<Cancelled>() => responder
           .send(lsp_server::Message::Response(lsp_server::Response {
             id,
             result: None,
@@ -384,6 +545,18 @@ impl RequestDispatcher<'_> {
             }),
           }))
           .unwrap(),
+        // Other errors, like a rename that isn't allowed, are shown to the user.
+        Err(e) => responder
+          .send(lsp_server::Message::Response(lsp_server::Response {
+            id,
+            result: None,
+            error: Some(lsp_server::ResponseError {
+              code:    ErrorCode::RequestFailed as i32,
+              message: e.to_string(),
+              data:    None,
+            }),
+          }))
+          .unwrap(),
       }))
       .unwrap();
 
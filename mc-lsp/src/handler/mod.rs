@@ -21,3 +21,23 @@ pub struct CanonicalModelParams {
 pub struct CanonicalModelResponse {
   pub model: mc_message::Model,
 }
+
+/// Reads a file that doesn't exist on disk, like a file within a jar. These
+/// files use the `mclsp:` URI scheme.
+pub enum ReadVirtualFile {}
+
+impl Request for ReadVirtualFile {
+  type Params = ReadVirtualFileParams;
+  type Result = Option<ReadVirtualFileResponse>;
+  const METHOD: &'static str = "mc-lsp/readVirtualFile";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadVirtualFileParams {
+  pub uri: Url,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadVirtualFileResponse {
+  pub text: String,
+}
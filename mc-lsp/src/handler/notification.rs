@@ -1,20 +1,25 @@
 use std::error::Error;
 
-use crate::{files::FileContent, global::GlobalState};
+use crate::{
+  files::FileContent,
+  global::GlobalState,
+  watch::{ChangeKind, FileChange},
+};
 
 pub fn handle_open_text_document(
   global: &mut GlobalState,
   params: lsp_types::DidOpenTextDocumentParams,
 ) -> Result<(), Box<dyn Error>> {
   if let Some(path) = global.absolute_path(&params.text_document.uri) {
-    let mut w = global.files.write();
+    // If this file was created before the watcher noticed, index it now.
+    if global.files.read().get_absolute(&path).is_none() {
+      global.apply_file_changes(vec![FileChange { path: path.clone(), kind: ChangeKind::Created }]);
+    }
 
-    // TODO: We should discover sources here, not when the server starts. For now,
-    // we only want this to do anything once the server has discovered all its
-    // files.
+    let mut w = global.files.write();
     let file_id = match w.get_absolute(&path) {
       Some(id) => id,
-      // We haven't indexed this file, so we don't care about it.
+      // This file isn't in any of the asset directories, so we don't care about it.
       None => return Ok(()),
     };
 
@@ -54,6 +59,31 @@ pub fn handle_save_text_document(
   Ok(())
 }
 
+pub fn handle_change_watched_files(
+  global: &mut GlobalState,
+  params: lsp_types::DidChangeWatchedFilesParams,
+) -> Result<(), Box<dyn Error>> {
+  let changes = params
+    .changes
+    .into_iter()
+    .filter_map(|event| {
+      let path = global.absolute_path(&event.uri)?;
+      let kind = match event.typ {
+        lsp_types::FileChangeType::CREATED => ChangeKind::Created,
+        lsp_types::FileChangeType::CHANGED => ChangeKind::Changed,
+        lsp_types::FileChangeType::DELETED => ChangeKind::Deleted,
+        _ => return None,
+      };
+
+      Some(FileChange { path, kind })
+    })
+    .collect();
+
+  global.apply_file_changes(changes);
+
+  Ok(())
+}
+
 fn apply_changes(
   mut file: String,
   changes: &[lsp_types::TextDocumentContentChangeEvent],
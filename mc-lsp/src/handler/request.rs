@@ -1,14 +1,16 @@
-use std::{collections::HashMap, error::Error, path::Path, sync::Arc};
+use std::{collections::HashMap, error::Error, sync::Arc};
 
 use line_index::LineIndex;
-use lsp_types::{SemanticTokenModifier, SemanticTokenType};
+use lsp_types::{notification::Notification, SemanticTokenModifier, SemanticTokenType, Url};
 use mc_analysis::{
   completion::CompletionKind,
   highlight::{Highlight, HighlightKind},
+  rename::TextEdit,
+  symbol::{DocumentSymbol, SymbolKind},
 };
-use mc_source::{FileId, FileLocation, TextRange, TextSize};
+use mc_source::{FileId, FileLocation, FileRange, FileType, TextRange, TextSize};
 
-use crate::global::GlobalStateSnapshot;
+use crate::{files::FileContent, global::GlobalStateSnapshot};
 
 /// Converts file positions to LSP positions.
 struct LspConverter {
@@ -46,7 +48,7 @@ pub fn handle_completion(
   snap: GlobalStateSnapshot,
   params: lsp_types::CompletionParams,
 ) -> Result<Option<lsp_types::CompletionResponse>, Box<dyn Error>> {
-  if let Some(_) = snap.absolute_path(&params.text_document_position.text_document.uri) {
+  if snap.files.read().uri_to_id(&params.text_document_position.text_document.uri).is_some() {
     let (cursor_pos, _) = LspConverter::from_pos(&snap, params.text_document_position)?;
     let completions = snap.analysis.completions(cursor_pos)?;
 
@@ -63,6 +65,7 @@ pub fn handle_completion(
             CompletionKind::Model => lsp_types::CompletionItemKind::CLASS,
             CompletionKind::Texture => lsp_types::CompletionItemKind::TEXT,
             CompletionKind::Namespace => lsp_types::CompletionItemKind::MODULE,
+            CompletionKind::Property => lsp_types::CompletionItemKind::ENUM_MEMBER,
           }),
 
           insert_text: Some(c.insert),
@@ -88,8 +91,8 @@ pub fn handle_semantic_tokens_full(
   snap: GlobalStateSnapshot,
   params: lsp_types::SemanticTokensParams,
 ) -> Result<Option<lsp_types::SemanticTokensResult>, Box<dyn Error>> {
-  if let Some(path) = snap.absolute_path(&params.text_document.uri) {
-    let file_id = snap.files.read().get_absolute(&path).ok_or("file not found")?;
+  let file_id = snap.files.read().uri_to_id(&params.text_document.uri);
+  if let Some(file_id) = file_id {
     let highlight = snap.analysis.highlight(file_id)?;
 
     let tokens = to_semantic_tokens(snap, file_id, &highlight)?;
@@ -108,28 +111,172 @@ pub fn handle_goto_definition(
   params: lsp_types::GotoDefinitionParams,
 ) -> Result<Option<lsp_types::GotoDefinitionResponse>, Box<dyn Error>> {
   let (cursor_pos, _) = LspConverter::from_pos(&snap, params.text_document_position_params)?;
-  let definition = snap.analysis.definition_for_name(cursor_pos)?;
+  let definitions = snap.analysis.definitions_for_name(cursor_pos)?;
 
-  if let Some(def) = definition {
-    let files = snap.files.read();
+  let mut locations = definitions
+    .into_iter()
+    .map(|def| file_range_location(&snap, def))
+    .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
 
-    Ok(Some(lsp_types::GotoDefinitionResponse::Scalar(lsp_types::Location::new(
-      lsp_types::Url::parse(&format!("file://{}", files.id_to_absolute_path(def.file).display()))
-        .unwrap(),
-      match def.range {
-        Some(range) => {
-          let converter = LspConverter::new(&snap, def.file)?;
-          converter.range(range)
-        }
-        None => lsp_types::Range {
-          start: lsp_types::Position::new(0, 0),
-          end:   lsp_types::Position::new(0, 0),
-        },
+  match locations.len() {
+    0 => Ok(None),
+    1 => Ok(Some(lsp_types::GotoDefinitionResponse::Scalar(locations.pop().unwrap()))),
+    _ => Ok(Some(lsp_types::GotoDefinitionResponse::Array(locations))),
+  }
+}
+
+pub fn handle_references(
+  snap: GlobalStateSnapshot,
+  params: lsp_types::ReferenceParams,
+) -> Result<Option<Vec<lsp_types::Location>>, Box<dyn Error>> {
+  let (cursor_pos, _) = LspConverter::from_pos(&snap, params.text_document_position)?;
+  let mut refs = snap.analysis.references_for_name(cursor_pos)?;
+
+  if params.context.include_declaration {
+    if let Some(def) = snap.analysis.definition_for_name(cursor_pos)? {
+      refs.insert(0, def);
+    }
+  }
+
+  let locations = refs
+    .into_iter()
+    .map(|r| file_range_location(&snap, r))
+    .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+  Ok(Some(locations))
+}
+
+/// Converts a range to an LSP location. Ranges without a `range` cover a whole
+/// file, and point to the start of it.
+fn file_range_location(
+  snap: &GlobalStateSnapshot,
+  range: FileRange,
+) -> Result<lsp_types::Location, Box<dyn Error>> {
+  Ok(lsp_types::Location::new(
+    snap.files.read().id_to_uri(range.file),
+    match range.range {
+      Some(r) => LspConverter::new(snap, range.file)?.range(r),
+      None => lsp_types::Range {
+        start: lsp_types::Position::new(0, 0),
+        end:   lsp_types::Position::new(0, 0),
       },
-    ))))
-  } else {
-    Ok(None)
+    },
+  ))
+}
+
+pub fn handle_prepare_rename(
+  snap: GlobalStateSnapshot,
+  params: lsp_types::TextDocumentPositionParams,
+) -> Result<Option<lsp_types::PrepareRenameResponse>, Box<dyn Error>> {
+  let (cursor_pos, converter) = LspConverter::from_pos(&snap, params)?;
+  let (range, name) = snap.analysis.prepare_rename(cursor_pos)??;
+
+  Ok(Some(lsp_types::PrepareRenameResponse::RangeWithPlaceholder {
+    range:       converter.range(range),
+    placeholder: name,
+  }))
+}
+
+pub fn handle_rename(
+  snap: GlobalStateSnapshot,
+  params: lsp_types::RenameParams,
+) -> Result<Option<lsp_types::WorkspaceEdit>, Box<dyn Error>> {
+  let (cursor_pos, _) = LspConverter::from_pos(&snap, params.text_document_position)?;
+  let change = snap.analysis.rename(cursor_pos, &params.new_name)??;
+
+  let mut edit = workspace_edit(&snap, change.edits)?;
+  if !change.moves.is_empty() {
+    // Moving files needs `document_changes`, and the edits must come first, as
+    // they refer to the files before they move.
+    let files = snap.files.read();
+    let mut operations = edit
+      .changes
+      .take()
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(uri, edits)| {
+        lsp_types::DocumentChangeOperation::Edit(lsp_types::TextDocumentEdit {
+          text_document: lsp_types::OptionalVersionedTextDocumentIdentifier { uri, version: None },
+          edits:         edits.into_iter().map(lsp_types::OneOf::Left).collect(),
+        })
+      })
+      .collect::<Vec<_>>();
+
+    for (file, path) in change.moves {
+      let new_uri =
+        files.moved_uri(file, &path).ok_or("cannot move a file outside of the project")?;
+      operations.push(lsp_types::DocumentChangeOperation::Op(lsp_types::ResourceOp::Rename(
+        lsp_types::RenameFile {
+          old_uri: files.id_to_uri(file),
+          new_uri,
+          options: None,
+          annotation_id: None,
+        },
+      )));
+    }
+
+    edit.document_changes = Some(lsp_types::DocumentChanges::Operations(operations));
   }
+
+  Ok(Some(edit))
+}
+
+pub fn handle_will_rename_files(
+  snap: GlobalStateSnapshot,
+  params: lsp_types::RenameFilesParams,
+) -> Result<Option<lsp_types::WorkspaceEdit>, Box<dyn Error>> {
+  let mut edits = vec![];
+  let mut errors = vec![];
+  for rename in params.files {
+    let (Ok(old_uri), Ok(new_uri)) = (Url::parse(&rename.old_uri), Url::parse(&rename.new_uri))
+    else {
+      continue;
+    };
+    let (Ok(old), Ok(new)) = (old_uri.to_file_path(), new_uri.to_file_path()) else { continue };
+
+    // Renaming a directory moves everything inside it.
+    let moved = snap.files.read().files_within(&old);
+    for (file, path) in moved {
+      let Ok(relative) = path.strip_prefix(&old) else { continue };
+      let new_path = if relative.as_os_str().is_empty() { new.clone() } else { new.join(relative) };
+      let Some(new_path) = snap.files.read().resource_path(&new_path) else { continue };
+
+      let rename = snap.analysis.rename_file(file, &new_path)?;
+      edits.extend(rename.edits);
+      errors.extend(rename.errors);
+    }
+  }
+
+  // The client moves the files either way, so the references that can't be
+  // updated are only reported.
+  if !errors.is_empty() {
+    let message = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n");
+    snap.sender.send(lsp_server::Message::Notification(lsp_server::Notification {
+      method: lsp_types::notification::ShowMessage::METHOD.into(),
+      params: serde_json::to_value(lsp_types::ShowMessageParams {
+        typ:     lsp_types::MessageType::WARNING,
+        message: format!("Some references can't be updated, and will break:\n{message}"),
+      })?,
+    }))?;
+  }
+
+  Ok(Some(workspace_edit(&snap, edits)?))
+}
+
+fn workspace_edit(
+  snap: &GlobalStateSnapshot,
+  edits: Vec<TextEdit>,
+) -> Result<lsp_types::WorkspaceEdit, Box<dyn Error>> {
+  let mut changes = HashMap::<_, Vec<_>>::new();
+  for edit in edits {
+    let converter = LspConverter::new(snap, edit.file)?;
+    changes
+      .entry(snap.files.read().id_to_uri(edit.file))
+      .or_default()
+      .push(lsp_types::TextEdit { range: converter.range(edit.range), new_text: edit.new_text });
+  }
+
+  Ok(lsp_types::WorkspaceEdit { changes: Some(changes), ..Default::default() })
 }
 
 pub fn handle_document_highlight(
@@ -151,9 +298,11 @@ pub fn handle_document_highlight(
       kind:  Some(lsp_types::DocumentHighlightKind::WRITE),
     };
 
-    let refs_highlight = refs.into_iter().map(|r| lsp_types::DocumentHighlight {
-      range: converter.range(r.range.unwrap()),
-      kind:  Some(lsp_types::DocumentHighlightKind::READ),
+    let refs_highlight = refs.into_iter().filter(|r| r.file == cursor_pos.file).map(|r| {
+      lsp_types::DocumentHighlight {
+        range: converter.range(r.range.unwrap()),
+        kind:  Some(lsp_types::DocumentHighlightKind::READ),
+      }
     });
 
     Ok(Some([def_highlight].into_iter().chain(refs_highlight).collect()))
@@ -162,34 +311,104 @@ pub fn handle_document_highlight(
   }
 }
 
+pub fn handle_document_symbol(
+  snap: GlobalStateSnapshot,
+  params: lsp_types::DocumentSymbolParams,
+) -> Result<Option<lsp_types::DocumentSymbolResponse>, Box<dyn Error>> {
+  let Some(file) = snap.files.read().uri_to_id(&params.text_document.uri) else { return Ok(None) };
+  let symbols = snap.analysis.document_symbols(file)?;
+  let converter = LspConverter::new(&snap, file)?;
+
+  Ok(Some(lsp_types::DocumentSymbolResponse::Nested(
+    symbols.into_iter().map(|s| to_lsp_symbol(&converter, s)).collect(),
+  )))
+}
+
+fn to_lsp_symbol(converter: &LspConverter, symbol: DocumentSymbol) -> lsp_types::DocumentSymbol {
+  #[allow(deprecated)]
+  lsp_types::DocumentSymbol {
+    name:            symbol.name,
+    detail:          symbol.detail,
+    kind:            match symbol.kind {
+      SymbolKind::Model => lsp_types::SymbolKind::CLASS,
+      SymbolKind::Texture => lsp_types::SymbolKind::VARIABLE,
+      SymbolKind::Element => lsp_types::SymbolKind::OBJECT,
+      SymbolKind::Face => lsp_types::SymbolKind::FIELD,
+      SymbolKind::Property => lsp_types::SymbolKind::ENUM,
+      SymbolKind::Variant => lsp_types::SymbolKind::ENUM_MEMBER,
+      SymbolKind::Multipart => lsp_types::SymbolKind::STRUCT,
+    },
+    tags:            None,
+    deprecated:      None,
+    range:           converter.range(symbol.range),
+    selection_range: converter.range(symbol.selection_range),
+    children:        Some(
+      symbol.children.into_iter().map(|s| to_lsp_symbol(converter, s)).collect(),
+    ),
+  }
+}
+
+/// The most results to return for a workspace symbol search. Clients search
+/// again as the query is typed, so there's no need to return every match.
+const WORKSPACE_SYMBOL_LIMIT: usize = 128;
+
+pub fn handle_workspace_symbol(
+  snap: GlobalStateSnapshot,
+  params: lsp_types::WorkspaceSymbolParams,
+) -> Result<Option<lsp_types::WorkspaceSymbolResponse>, Box<dyn Error>> {
+  let symbols = snap.analysis.workspace_symbols(&params.query, WORKSPACE_SYMBOL_LIMIT)?;
+
+  let files = snap.files.read();
+  Ok(Some(lsp_types::WorkspaceSymbolResponse::Nested(
+    symbols
+      .into_iter()
+      .map(|symbol| {
+        let (kind, label) = match symbol.ty {
+          FileType::Model => (lsp_types::SymbolKind::CLASS, "model"),
+          FileType::Blockstate => (lsp_types::SymbolKind::ENUM, "blockstate"),
+          FileType::Texture => (lsp_types::SymbolKind::FILE, "texture"),
+          FileType::TextureMeta => (lsp_types::SymbolKind::FILE, "texture metadata"),
+          FileType::PackMeta => (lsp_types::SymbolKind::PACKAGE, "pack metadata"),
+        };
+        let namespace = symbol.name.split_once(':').map_or("", |(n, _)| n);
+
+        lsp_types::WorkspaceSymbol {
+          container_name: Some(format!("{label} in {namespace}")),
+          name: symbol.name,
+          kind,
+          tags: None,
+          location: lsp_types::OneOf::Left(lsp_types::Location::new(
+            files.id_to_uri(symbol.file),
+            lsp_types::Range::default(),
+          )),
+          data: None,
+        }
+      })
+      .collect(),
+  )))
+}
+
 pub fn handle_hover(
-  _snap: GlobalStateSnapshot,
-  _params: lsp_types::HoverParams,
+  snap: GlobalStateSnapshot,
+  params: lsp_types::HoverParams,
 ) -> Result<Option<lsp_types::Hover>, Box<dyn Error>> {
-  Ok(None)
-  /*
   let (pos, converter) = LspConverter::from_pos(&snap, params.text_document_position_params)?;
-  let def = snap.analysis.definition_for_name(pos)?;
-  let ty = snap.analysis.type_at(pos)?;
-
-  let range = def.map(|(_, pos)| converter.range(pos.range));
+  let Some(hover) = snap.analysis.hover(pos)? else { return Ok(None) };
 
   Ok(Some(lsp_types::Hover {
-    range,
-    contents: lsp_types::HoverContents::Scalar(lsp_types::MarkedString::String(match ty {
-      Some(ty) => ty.to_string(),
-      None => "unknown type".to_string(),
-    })),
+    range:    Some(converter.range(hover.range)),
+    contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+      kind:  lsp_types::MarkupKind::Markdown,
+      value: hover.contents,
+    }),
   }))
-  */
 }
 
 pub fn handle_code_action(
   snap: GlobalStateSnapshot,
   params: lsp_types::CodeActionParams,
 ) -> Result<Option<Vec<lsp_types::CodeActionOrCommand>>, Box<dyn Error>> {
-  let path = Path::new(params.text_document.uri.path());
-  if let Some(file) = snap.files.read().get_absolute(path) {
+  if let Some(file) = snap.files.read().uri_to_id(&params.text_document.uri) {
     let diagnostics = snap.analysis.diagnostics(file)?;
     let converter = LspConverter::new(&snap, file)?;
 
@@ -240,8 +459,7 @@ pub fn handle_canonical_model(
   snap: GlobalStateSnapshot,
   params: super::CanonicalModelParams,
 ) -> Result<Option<super::CanonicalModelResponse>, Box<dyn Error>> {
-  let path = Path::new(params.uri.path());
-  if let Some(file) = snap.files.read().get_absolute(path) {
+  if let Some(file) = snap.files.read().uri_to_id(&params.uri) {
     let model = snap.analysis.canonical_model(file)?;
 
     Ok(Some(super::CanonicalModelResponse { model }))
@@ -250,6 +468,21 @@ pub fn handle_canonical_model(
   }
 }
 
+pub fn handle_read_virtual_file(
+  snap: GlobalStateSnapshot,
+  params: super::ReadVirtualFileParams,
+) -> Result<Option<super::ReadVirtualFileResponse>, Box<dyn Error>> {
+  let files = snap.files.read();
+  if let Some(file) = files.uri_to_id(&params.uri) {
+    match files.read(file) {
+      FileContent::Json(text) => Ok(Some(super::ReadVirtualFileResponse { text })),
+      FileContent::Png(_) => Ok(None),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
 struct TokenModifier {
   stat: bool,
 }
@@ -334,8 +567,7 @@ fn file_position(
 ) -> Result<FileLocation, Box<dyn Error>> {
   let files = snap.files.read();
 
-  let path = Path::new(pos.text_document.uri.path());
-  let file_id = files.get_absolute(path).ok_or("file not found")?;
+  let file_id = files.uri_to_id(&pos.text_document.uri).ok_or("file not found")?;
 
   let index = snap.analysis.line_index(file_id)?;
 
@@ -27,10 +27,38 @@ pub fn server_capabilities() -> lsp_types::ServerCapabilities {
 
     document_highlight_provider: Some(lsp_types::OneOf::Left(true)),
 
+    references_provider: Some(lsp_types::OneOf::Left(true)),
+
+    rename_provider: Some(lsp_types::OneOf::Right(lsp_types::RenameOptions {
+      prepare_provider:           Some(true),
+      work_done_progress_options: Default::default(),
+    })),
+
+    document_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+    workspace_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+
     hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
 
     code_action_provider: Some(lsp_types::CodeActionProviderCapability::Simple(true)),
 
+    workspace: Some(lsp_types::WorkspaceServerCapabilities {
+      file_operations: Some(lsp_types::WorkspaceFileOperationsServerCapabilities {
+        will_rename: Some(lsp_types::FileOperationRegistrationOptions {
+          // Directories are included, as moving one moves every model inside it.
+          filters: vec![lsp_types::FileOperationFilter {
+            scheme:  Some("file".into()),
+            pattern: lsp_types::FileOperationPattern {
+              glob:    "**/assets/**".into(),
+              matches: None,
+              options: None,
+            },
+          }],
+        }),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }),
+
     ..Default::default()
   }
 }
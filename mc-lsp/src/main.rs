@@ -1,11 +1,15 @@
 use std::{error::Error, fs, path::PathBuf};
 
+use mc_source::Layer;
+
+mod config;
 mod files;
 mod global;
 mod handler;
 mod info;
 mod progress;
 mod search;
+mod watch;
 
 #[macro_use]
 extern crate log;
@@ -37,8 +41,8 @@ fn run() -> Result<(), Box<dyn Error>> {
       return Err(e.into());
     }
   };
-  // TODO: Check client capabilities.
-  serde_json::from_value::<lsp_types::InitializeParams>(initialize_params)?;
+  let initialize_params = serde_json::from_value::<lsp_types::InitializeParams>(initialize_params)?;
+  let client_can_watch = watch::client_can_watch(&initialize_params.capabilities);
 
   let server_capabilities = info::server_capabilities();
 
@@ -59,27 +63,68 @@ fn run() -> Result<(), Box<dyn Error>> {
     return Err(e.into());
   }
 
-  let mut global = global::GlobalState::new(connection.sender);
+  let config = config::Config::load(
+    workspace_root(&initialize_params)?,
+    initialize_params.initialization_options,
+  );
+  info!("loaded config: {:?}", config);
+
+  let mut global = global::GlobalState::new(connection.sender, config.clone());
 
-  let mut workspace = crate::search::discover_workspace(&mut global.files.write());
+  let mut workspace = crate::search::discover_workspace(&config, &mut global.files.write());
+
+  for jar in config.dependency_jars() {
+    info!("indexing dependency jar: {}", jar.display());
+    search::add_archive(&mut workspace, &mut global.files.write(), &jar, Layer::Dependency);
+  }
 
-  match mc_gradle::extract_jar() {
-    Ok(path) => {
-      info!("extracted minecraft jar to: {}", path.display());
-      search::add_client_path(&mut workspace, &mut global.files.write(), &path);
+  match config.minecraft_version.clone().or_else(|| mc_gradle::detect_version(&config.root)) {
+    Some(version) => {
+      workspace.minecraft_version = version.parse().ok();
+
+      match mc_gradle::find_client_jar(&version) {
+        Ok(path) => {
+          info!("using minecraft {} jar at: {}", version, path.display());
+          search::add_archive(&mut workspace, &mut global.files.write(), &path, Layer::Vanilla);
+        }
+        Err(e) => {
+          error!("failed to find minecraft jar: {}", e);
+        }
+      }
     }
-    Err(e) => {
-      error!("failed to extract minecraft jar: {}", e);
+    None => {
+      error!("could not detect the minecraft version, set `minecraft_version` in mclsp.toml");
     }
   }
 
   global.set_workspace(workspace);
 
+  if client_can_watch {
+    global.watch_with_client();
+  } else {
+    global.watch_locally();
+  }
+
   global.run(connection.receiver)?;
 
   Ok(())
 }
 
+/// Picks the workspace root from the initialize params, falling back to the
+/// current directory.
+fn workspace_root(params: &lsp_types::InitializeParams) -> Result<PathBuf, Box<dyn Error>> {
+  #[allow(deprecated)]
+  let uri = match params.workspace_folders {
+    Some(ref folders) if !folders.is_empty() => Some(&folders[0].uri),
+    _ => params.root_uri.as_ref(),
+  };
+
+  match uri.and_then(|uri| uri.to_file_path().ok()) {
+    Some(path) => Ok(path),
+    None => Ok(std::env::current_dir()?),
+  }
+}
+
 fn setup_logging() {
   let dir = PathBuf::from(std::env::var("HOME").unwrap()).join(".cache").join("mclsp");
   fs::create_dir_all(&dir).unwrap();
@@ -1,28 +1,250 @@
 //! Converts files and a BSP workspace into FileIds and SourceRootIds.
 
-use std::{io, path, path::PathBuf};
+use std::{collections::HashSet, io, path, path::PathBuf};
 
-use mc_source::{File, FileType, Path, Workspace};
+use mc_source::{File, FileId, FileType, Layer, Path, Workspace};
 
-use crate::files::{FileContent, Files};
+use crate::{
+  config::Config,
+  files::{FileContent, Files},
+};
 
-pub fn discover_workspace(files: &mut Files) -> Workspace {
-  // We assume the root is the current directory. Then, we search for assets.
+pub fn discover_workspace(config: &Config, files: &mut Files) -> Workspace {
+  let mut workspace = Workspace::default();
 
-  let mut workspace = Workspace { namespaces: vec![] };
+  if config.assets.is_empty() {
+    warn!("no asset directories found in {}", config.root.display());
+  }
 
   // FIXME: We shouldn't index files here! We should index them when they're
   // opened.
-  discover_assets_in(&mut workspace, files, path::Path::new("./common/src/main/resources/assets"));
+  for (path, layer) in config.asset_roots() {
+    info!("indexing assets in {}", path.display());
+    if path.is_file() {
+      add_archive(&mut workspace, files, path, layer);
+    } else {
+      discover_assets_in(&mut workspace, files, path, layer);
+    }
+  }
+
+  for path in config.pack_files().filter(|p| p.is_file()) {
+    add_pack(&mut workspace, files, &path);
+  }
 
   workspace
 }
 
-pub fn add_client_path(workspace: &mut Workspace, files: &mut Files, path: &path::Path) {
-  discover_assets_in(workspace, files, &path.join("assets"));
+/// Adds a `pack.mcmeta` file to the workspace, or re-reads it if it has already
+/// been added.
+fn add_pack(workspace: &mut Workspace, files: &mut Files, path: &path::Path) -> Option<FileId> {
+  let content = match read_file(path, FileType::PackMeta) {
+    Ok(content) => content,
+    Err(e) => {
+      error!("failed to read {}: {}", path.display(), e);
+      return None;
+    }
+  };
+
+  let id = match files.get_absolute(path) {
+    Some(id) => id,
+    None => files.create(path),
+  };
+  if files.read(id) != content {
+    files.write(id, content);
+  }
+
+  if !workspace.packs.contains(&id) {
+    workspace.packs.push(id);
+  }
+
+  Some(id)
+}
+
+/// Mounts a jar (or any zip), and adds all the namespaces in its `assets`
+/// directory to the workspace. Nothing is read from the jar until its needed.
+pub fn add_archive(workspace: &mut Workspace, files: &mut Files, path: &path::Path, layer: Layer) {
+  let archive = match files.mount_archive(path) {
+    Ok(archive) => archive,
+    Err(e) => {
+      error!("failed to open archive {}: {}", path.display(), e);
+      return;
+    }
+  };
+
+  let mut namespaces = Vec::<mc_source::Namespace>::new();
+
+  for entry in files.archive_entries(archive) {
+    let mut segments = entry.split('/');
+    if segments.next() != Some("assets") {
+      continue;
+    }
+    let Some(namespace) = segments.next() else { continue };
+
+    let mut relative = Path::new_namespace(namespace.to_string());
+    relative.segments = segments.map(|s| s.to_string()).collect();
+
+    let Some(ty) = file_type(&relative) else { continue };
+
+    let id = files.create_archived(archive, &entry);
+
+    let index = match namespaces.iter().position(|n| n.name == namespace) {
+      Some(i) => i,
+      None => {
+        namespaces.push(mc_source::Namespace { name: namespace.to_string(), layer, files: vec![] });
+        namespaces.len() - 1
+      }
+    };
+    namespaces[index].files.push(File { id, ty, path: relative });
+  }
+
+  workspace.namespaces.extend(namespaces);
+}
+
+/// Adds the file (or all the files in the directory) at `path` to the
+/// workspace, if it is within one of the asset directories. If the file has
+/// already been added, its content is re-read from disk. Returns all the files
+/// that were added or changed.
+pub fn add_path(
+  config: &Config,
+  workspace: &mut Workspace,
+  files: &mut Files,
+  path: &path::Path,
+) -> Vec<(FileId, FileType)> {
+  let mut added = vec![];
+
+  if path.is_dir() {
+    let Ok(dir) = std::fs::read_dir(path) else { return added };
+    for entry in dir.filter_map(|e| e.ok()) {
+      added.extend(add_path(config, workspace, files, &entry.path()));
+    }
+    return added;
+  }
+
+  let Ok(path) = path.canonicalize() else { return added };
+
+  if config.pack_files().any(|p| p.canonicalize().is_ok_and(|p| p == path)) {
+    added.extend(add_pack(workspace, files, &path).map(|id| (id, FileType::PackMeta)));
+    return added;
+  }
+
+  let Some((layer, root, relative)) = resolve_asset_path(config, &path) else { return added };
+  let Some(ty) = file_type(&relative) else { return added };
+
+  let content = match read_file(&path, ty) {
+    Ok(content) => content,
+    Err(e) => {
+      error!("failed to read {}: {}", path.display(), e);
+      return added;
+    }
+  };
+
+  files.add_namespace(root);
+  let id = match files.get_absolute(&path) {
+    Some(id) => id,
+    None => files.create(&path),
+  };
+  if files.read(id) != content {
+    files.write(id, content);
+  }
+
+  let index = match workspace
+    .namespaces
+    .iter()
+    .position(|n| n.name == relative.namespace && n.layer == layer)
+  {
+    Some(i) => i,
+    None => {
+      workspace.namespaces.push(mc_source::Namespace {
+        name: relative.namespace.clone(),
+        layer,
+        files: vec![],
+      });
+      workspace.namespaces.len() - 1
+    }
+  };
+
+  let namespace = &mut workspace.namespaces[index];
+  if !namespace.files.iter().any(|f| f.id == id) {
+    namespace.files.push(File { id, ty, path: relative });
+  }
+  added.push((id, ty));
+
+  added
+}
+
+/// Removes the file at `path` from the workspace. If `path` is a directory,
+/// every file within it is removed. Returns the removed files.
+pub fn remove_path(workspace: &mut Workspace, files: &Files, path: &path::Path) -> Vec<FileId> {
+  let mut removed = vec![];
+
+  // Files in archives can't be deleted, so only files on disk are checked.
+  let within = files.files_within(path).into_iter().map(|(id, _)| id).collect::<HashSet<_>>();
+  let is_removed = |id: FileId| within.contains(&id);
+
+  workspace.packs.retain(|&id| {
+    if is_removed(id) {
+      removed.push(id);
+      false
+    } else {
+      true
+    }
+  });
+
+  for namespace in &mut workspace.namespaces {
+    namespace.files.retain(|f| {
+      if is_removed(f.id) {
+        removed.push(f.id);
+        false
+      } else {
+        true
+      }
+    });
+  }
+
+  removed
+}
+
+/// Finds the asset directory that `path` is within. Returns the layer of that
+/// directory, the root of the namespace, and the path relative to the
+/// namespace.
+fn resolve_asset_path(config: &Config, path: &path::Path) -> Option<(Layer, PathBuf, Path)> {
+  config.asset_roots().filter(|(dir, _)| dir.is_dir()).find_map(|(dir, layer)| {
+    let dir = dir.canonicalize().ok()?;
+    let mut components = path.strip_prefix(&dir).ok()?.iter();
+
+    let name = components.next()?.to_string_lossy().into_owned();
+    let mut relative = Path::new_namespace(name.clone());
+    relative.segments = components.map(|s| s.to_string_lossy().into_owned()).collect();
+
+    Some((layer, dir.join(name), relative))
+  })
 }
 
-fn discover_assets_in(workspace: &mut Workspace, files: &mut Files, path: &path::Path) {
+fn file_type(relative: &Path) -> Option<FileType> {
+  let name = relative.segments.last()?;
+
+  match relative.segments.first().map(|s| s.as_str()) {
+    Some("models") if name.ends_with(".json") => Some(FileType::Model),
+    Some("blockstates") if name.ends_with(".json") => Some(FileType::Blockstate),
+    Some("textures") if name.ends_with(".png") => Some(FileType::Texture),
+    Some("textures") if name.ends_with(".png.mcmeta") => Some(FileType::TextureMeta),
+    _ => None,
+  }
+}
+
+fn read_file(path: &path::Path, ty: FileType) -> io::Result<FileContent> {
+  match ty {
+    FileType::Texture => std::fs::read(path).map(FileContent::Png),
+    _ => std::fs::read_to_string(path).map(FileContent::Json),
+  }
+}
+
+fn discover_assets_in(
+  workspace: &mut Workspace,
+  files: &mut Files,
+  path: &path::Path,
+  layer: Layer,
+) {
   let dir = match std::fs::read_dir(path) {
     Ok(dir) => dir,
     Err(e) => {
@@ -38,12 +260,14 @@ fn discover_assets_in(workspace: &mut Workspace, files: &mut Files, path: &path:
 
     let mut sources = vec![];
     let root_path: PathBuf = path.join(&name).canonicalize().unwrap();
-    files.add_namespace(rel_path.namespace.clone(), root_path.clone());
+    files.add_namespace(root_path.clone());
     discover_sources(&root_path, &rel_path, &mut sources, files).unwrap();
 
-    workspace
-      .namespaces
-      .push(mc_source::Namespace { name: name.to_string_lossy().into_owned(), files: sources });
+    workspace.namespaces.push(mc_source::Namespace {
+      name: name.to_string_lossy().into_owned(),
+      layer,
+      files: sources,
+    });
   }
 }
 
@@ -63,36 +287,56 @@ fn discover_sources(
     if path.is_dir() {
       discover_sources(path.as_path(), &relative, sources, files)?;
     } else {
-      let ty = match relative.segments.first().map(|s| s.as_str()) {
-        Some("models") => FileType::Model,
-        Some("blockstates") => FileType::Blockstate,
-        Some("textures") => FileType::Model, // FIXME: `ty` shouldn't exist on textures.
-        _ => continue,
-      };
+      let Some(ty) = file_type(&relative) else { continue };
 
       match files.get_absolute(&path) {
         Some(id) => {
           sources.push(File { id, ty, path: relative.clone() });
         }
-        None => match path.extension() {
-          Some(ext) if ext == "json" => {
-            let id = files.create(&path);
-            let content = std::fs::read_to_string(&path)?;
-            files.write(id, FileContent::Json(content));
-            sources.push(File { id, ty, path: relative });
-          }
-          Some(ext) if ext == "png" => {
-            let id = files.create(&path);
-            let content = std::fs::read(&path)?;
-            files.write(id, FileContent::Png(content));
-            // FIXME: This `ty` shouldn't exist on textures.
-            sources.push(File { id, ty: FileType::Model, path: relative });
-          }
-          _ => {}
-        },
+        None => {
+          let id = files.create(&path);
+          files.write(id, read_file(&path, ty)?);
+          sources.push(File { id, ty, path: relative });
+        }
       }
     }
   }
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn add_and_remove_paths() {
+    let root = std::env::temp_dir().join("mclsp-search-add-and-remove-paths");
+    let _ = std::fs::remove_dir_all(&root);
+    let assets = root.join("src/main/resources/assets");
+    std::fs::create_dir_all(assets.join("foo/models/block")).unwrap();
+
+    let config = Config::load(root.clone(), None);
+    let mut files = Files::new();
+    let mut workspace = discover_workspace(&config, &mut files);
+    assert_eq!(workspace.namespaces[0].files.len(), 0);
+
+    let model = assets.join("foo/models/block/bar.json");
+    std::fs::write(&model, "{}").unwrap();
+    let added = add_path(&config, &mut workspace, &mut files, &model);
+    assert_eq!(added.len(), 1);
+    assert_eq!(workspace.namespaces[0].files.len(), 1);
+    assert_eq!(workspace.namespaces[0].files[0].path, "foo:models/block/bar.json".parse().unwrap());
+
+    // Adding it again only re-reads it.
+    add_path(&config, &mut workspace, &mut files, &model);
+    assert_eq!(workspace.namespaces[0].files.len(), 1);
+
+    std::fs::remove_file(&model).unwrap();
+    let removed = remove_path(&mut workspace, &files, &assets.canonicalize().unwrap().join("foo"));
+    assert_eq!(removed, vec![added[0].0]);
+    assert_eq!(workspace.namespaces[0].files.len(), 0);
+
+    std::fs::remove_dir_all(&root).unwrap();
+  }
+}
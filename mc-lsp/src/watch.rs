@@ -0,0 +1,122 @@
+//! Watches the asset directories for files being created, changed, and
+//! deleted. If the client supports it, we ask the client to watch files for us
+//! with `workspace/didChangeWatchedFiles`. Otherwise, we watch them ourselves.
+
+use std::path::{Path, PathBuf};
+
+use crossbeam_channel::Sender;
+use lsp_types::notification::Notification;
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+  Created,
+  Changed,
+  Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+  pub path: PathBuf,
+  pub kind: ChangeKind,
+}
+
+/// Returns `true` if the client can watch files for us.
+pub fn client_can_watch(capabilities: &lsp_types::ClientCapabilities) -> bool {
+  capabilities
+    .workspace
+    .as_ref()
+    .and_then(|w| w.did_change_watched_files.as_ref())
+    .and_then(|w| w.dynamic_registration)
+    .unwrap_or(false)
+}
+
+/// Returns the registration that asks the client to send
+/// `workspace/didChangeWatchedFiles` for all the asset directories.
+pub fn client_watcher_registration(config: &Config) -> lsp_types::RegistrationParams {
+  let dirs = asset_dirs(config).map(|dir| format!("{}/**/*", dir.display()));
+  let packs = config.pack_files().map(|path| path.display().to_string());
+  let watchers = dirs
+    .chain(packs)
+    .map(|glob| lsp_types::FileSystemWatcher {
+      glob_pattern: lsp_types::GlobPattern::String(glob),
+      kind:         None,
+    })
+    .collect();
+
+  let options = lsp_types::DidChangeWatchedFilesRegistrationOptions { watchers };
+
+  lsp_types::RegistrationParams {
+    registrations: vec![lsp_types::Registration {
+      id:               "mclsp-watch-assets".into(),
+      method:           lsp_types::notification::DidChangeWatchedFiles::METHOD.into(),
+      register_options: Some(serde_json::to_value(options).unwrap()),
+    }],
+  }
+}
+
+/// Watches all the asset directories on a background thread. The watcher stops
+/// when it is dropped.
+pub fn spawn_local_watcher(
+  config: &Config,
+  sender: Sender<Vec<FileChange>>,
+) -> notify::Result<notify::RecommendedWatcher> {
+  let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+    let event = match res {
+      Ok(event) => event,
+      Err(e) => {
+        error!("file watcher error: {}", e);
+        return;
+      }
+    };
+
+    let changes = event
+      .paths
+      .into_iter()
+      .filter_map(|path| {
+        let kind = match event.kind {
+          EventKind::Create(_) => ChangeKind::Created,
+          EventKind::Remove(_) => ChangeKind::Deleted,
+          // Renames come in all sorts of forms depending on the platform, so just
+          // check if the path still exists.
+          EventKind::Modify(notify::event::ModifyKind::Name(_)) => {
+            if path.exists() {
+              ChangeKind::Created
+            } else {
+              ChangeKind::Deleted
+            }
+          }
+          EventKind::Modify(_) => ChangeKind::Changed,
+          _ => return None,
+        };
+
+        Some(FileChange { path, kind })
+      })
+      .collect::<Vec<_>>();
+
+    if !changes.is_empty() {
+      // The server may have shut down, in which case we don't care.
+      let _ = sender.send(changes);
+    }
+  })?;
+
+  for dir in asset_dirs(config) {
+    info!("watching {}", dir.display());
+    watcher.watch(dir, RecursiveMode::Recursive)?;
+  }
+  // Watch the directory containing `pack.mcmeta`, so we notice when its created.
+  for path in config.pack_files() {
+    if let Some(dir) = path.parent() {
+      watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+  }
+
+  Ok(watcher)
+}
+
+/// Archives never change, so this only returns directories.
+fn asset_dirs(config: &Config) -> impl Iterator<Item = &Path> {
+  config.asset_roots().map(|(path, _)| path).filter(|path| path.is_dir())
+}
@@ -9,6 +9,8 @@ use serde::{
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Model {
   pub elements: Vec<Element>,
+  #[serde(default)]
+  pub display:  Display,
 }
 
 impl Model {
@@ -17,6 +19,38 @@ impl Model {
   }
 }
 
+/// The transforms for each display context. The left hand contexts fall back
+/// to the right hand, and any other context that isn't set uses the identity
+/// transform.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Display {
+  #[serde(default)]
+  pub thirdperson_righthand: Option<Transform>,
+  #[serde(default)]
+  pub thirdperson_lefthand:  Option<Transform>,
+  #[serde(default)]
+  pub firstperson_righthand: Option<Transform>,
+  #[serde(default)]
+  pub firstperson_lefthand:  Option<Transform>,
+  #[serde(default)]
+  pub head:                  Option<Transform>,
+  #[serde(default)]
+  pub gui:                   Option<Transform>,
+  #[serde(default)]
+  pub ground:                Option<Transform>,
+  #[serde(default)]
+  pub fixed:                 Option<Transform>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Transform {
+  /// The rotation around each axis, in degrees.
+  pub rotation:    Pos,
+  /// The translation, in 1/16ths of a block.
+  pub translation: Pos,
+  pub scale:       Pos,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Element {
   pub from:     Pos,
@@ -51,9 +85,34 @@ impl Faces {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Face {
-  pub uv:      [f64; 4],
+  pub uv:        [f64; 4],
   /// If there is no texture, an error texture should be rendered.
-  pub texture: Option<String>,
+  pub texture:   Option<String>,
+  /// Rotates the texture clockwise by 0, 90, 180, or 270 degrees.
+  #[serde(default)]
+  pub rotation:  u32,
+  /// Faces with a tint index are colored by the block, like grass and leaves.
+  #[serde(default)]
+  pub tintindex: Option<i32>,
+  /// The side of the block that hides this face when it is covered.
+  #[serde(default)]
+  pub cullface:  Option<Direction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+  #[serde(rename = "north")]
+  North,
+  #[serde(rename = "south")]
+  South,
+  #[serde(rename = "east")]
+  East,
+  #[serde(rename = "west")]
+  West,
+  #[serde(rename = "up")]
+  Up,
+  #[serde(rename = "down")]
+  Down,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
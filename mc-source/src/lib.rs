@@ -1,13 +1,17 @@
-use std::sync::Arc;
+use std::{collections::HashMap, fmt, sync::Arc};
 
 use mc_syntax::Parse;
 
 mod path;
+mod png;
 mod resolved;
+mod version;
 
 pub use line_index::{TextRange, TextSize};
 pub use path::Path;
+pub use png::{ColorType, PngHeader};
 pub use resolved::{ModelPath, ResolvedPath, TexturePath};
+pub use version::Version;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FileLocation {
@@ -15,14 +19,21 @@ pub struct FileLocation {
   pub index: TextSize,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FileRange {
   pub file:  FileId,
   pub range: Option<TextRange>,
 }
 
+/// Reads binary files, like textures. These aren't stored in the database
+/// up front, as there are thousands of them in the vanilla jar, and most are
+/// never looked at.
+pub trait FileLoader {
+  fn load_bytes(&self, file_id: FileId) -> Arc<[u8]>;
+}
+
 #[salsa::query_group(SourceDatabaseStorage)]
-pub trait SourceDatabase: std::fmt::Debug {
+pub trait SourceDatabase: FileLoader + std::fmt::Debug {
   /// The current workspace.
   #[salsa::input]
   fn workspace(&self) -> Arc<Workspace>;
@@ -31,20 +42,57 @@ pub trait SourceDatabase: std::fmt::Debug {
   #[salsa::input]
   fn file_text(&self, file_id: FileId) -> Arc<str>;
 
+  /// The revision when a binary file was added or last changed. Setting this
+  /// makes `file_bytes` read the file again.
+  #[salsa::input]
+  fn file_revision(&self, file_id: FileId) -> u64;
+
+  /// Returns the current content of a binary file. This is only used for
+  /// textures, which are read the first time this is called.
+  fn file_bytes(&self, file_id: FileId) -> Arc<[u8]>;
+
   #[salsa::input]
   fn file_type(&self, file_id: FileId) -> FileType;
 
-  /// Parses the file into the syntax tree.
+  /// Parses the file into the syntax tree. This must only be called on JSON
+  /// files.
   fn parse_json(&self, file_id: FileId) -> Parse<mc_syntax::Json>;
+
+  /// Reads the header of a texture. Returns `None` if the file isn't a valid
+  /// PNG.
+  fn png_header(&self, file_id: FileId) -> Option<PngHeader>;
+
+  /// Every file in the workspace, grouped by path. This is built in one pass
+  /// over the workspace, so that looking up a path doesn't need to scan every
+  /// namespace.
+  fn path_index(&self) -> Arc<PathIndex>;
+
+  /// Returns every file with the given path, across all layers. The first file
+  /// is the one that is actually used, and the rest are shadowed by it.
+  fn lookup_path(&self, path: Path) -> Vec<FileId>;
+
+  /// Returns every file with the same path as the given file, across all
+  /// layers, including itself.
+  fn file_layers(&self, file_id: FileId) -> Vec<FileId>;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FileType {
   Model,
   Blockstate,
+  Texture,
+  /// A `.png.mcmeta` file, next to a texture.
+  TextureMeta,
+  /// A `pack.mcmeta` file, at the root of a resource pack.
+  PackMeta,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+impl FileType {
+  /// Returns `true` for files that are parsed as JSON.
+  pub fn is_json(&self) -> bool { !matches!(self, FileType::Texture) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FileId(u32);
 
 impl FileId {
@@ -52,20 +100,44 @@ impl FileId {
   pub const fn new_raw(id: u32) -> Self { FileId(id) }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Workspace {
   pub namespaces: Vec<Namespace>,
+
+  /// The `pack.mcmeta` files at the root of each of the project's resource
+  /// directories.
+  pub packs: Vec<FileId>,
+
+  /// The minecraft version from the build scripts or config, if known. Prefer
+  /// `HirDatabase::minecraft_version`, which also checks `pack.mcmeta`.
+  pub minecraft_version: Option<Version>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Namespace {
-  pub name: String,
+  pub name:  String,
+  pub layer: Layer,
 
   /// Files and their relative paths.
   pub files: Vec<File>,
 }
 
-#[derive(Debug)]
+/// Where a namespace comes from. When multiple layers define the same file,
+/// the file from the highest priority layer (the first variant) is used.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+  /// Files in the project itself.
+  #[default]
+  Project,
+
+  /// Files from mod dependencies, or other read-only roots.
+  Dependency,
+
+  /// Files from the vanilla client jar.
+  Vanilla,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct File {
   pub id:   FileId,
   pub ty:   FileType,
@@ -76,7 +148,96 @@ impl File {
   pub fn resolved_path(&self) -> Option<ResolvedPath> { ResolvedPath::parse(&self.path) }
 }
 
+impl Workspace {
+  /// Returns all the namespaces with the given name, in priority order.
+  pub fn namespaces_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Namespace> {
+    let mut namespaces = self.namespaces.iter().filter(|n| n.name == name).collect::<Vec<_>>();
+    // NB: This is a stable sort, so namespaces within a layer keep their order.
+    namespaces.sort_by_key(|n| n.layer);
+    namespaces.into_iter()
+  }
+
+  /// Returns `true` if the given file is part of the workspace.
+  pub fn contains(&self, id: FileId) -> bool { self.packs.contains(&id) || self.file(id).is_some() }
+
+  /// Finds a file, and the namespace it is in.
+  pub fn file(&self, id: FileId) -> Option<(&Namespace, &File)> {
+    self.namespaces.iter().find_map(|n| n.files.iter().find(|f| f.id == id).map(|f| (n, f)))
+  }
+}
+
+/// Every file in the workspace, by path and by ID.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PathIndex {
+  /// The files with each path, in priority order.
+  paths: HashMap<Path, Vec<FileId>>,
+  files: HashMap<FileId, File>,
+}
+
+impl PathIndex {
+  /// Returns every file with the given path, in priority order.
+  pub fn lookup(&self, path: &Path) -> &[FileId] {
+    self.paths.get(path).map(Vec::as_slice).unwrap_or_default()
+  }
+
+  pub fn file(&self, id: FileId) -> Option<&File> { self.files.get(&id) }
+
+  /// Returns the file that is used for each path, skipping any files that are
+  /// overridden by a higher priority layer.
+  pub fn used_files(&self) -> impl Iterator<Item = &File> {
+    self.paths.values().filter_map(|ids| self.files.get(ids.first()?))
+  }
+}
+
+impl fmt::Display for Layer {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Layer::Project => write!(f, "the project"),
+      Layer::Dependency => write!(f, "a dependency"),
+      Layer::Vanilla => write!(f, "vanilla"),
+    }
+  }
+}
+
 fn parse_json(db: &dyn SourceDatabase, file_id: FileId) -> Parse<mc_syntax::Json> {
   let text = db.file_text(file_id);
   mc_syntax::Json::parse(&text)
 }
+
+fn file_bytes(db: &dyn SourceDatabase, file_id: FileId) -> Arc<[u8]> {
+  db.file_revision(file_id);
+  db.load_bytes(file_id)
+}
+
+fn png_header(db: &dyn SourceDatabase, file_id: FileId) -> Option<PngHeader> {
+  PngHeader::parse(&db.file_bytes(file_id))
+}
+
+fn path_index(db: &dyn SourceDatabase) -> Arc<PathIndex> {
+  let workspace = db.workspace();
+
+  let mut namespaces = workspace.namespaces.iter().collect::<Vec<_>>();
+  // NB: This is a stable sort, so namespaces within a layer keep their order.
+  namespaces.sort_by_key(|n| n.layer);
+
+  let mut index = PathIndex::default();
+  for file in namespaces.into_iter().flat_map(|n| n.files.iter()) {
+    index.paths.entry(file.path.clone()).or_default().push(file.id);
+    index.files.insert(file.id, file.clone());
+  }
+
+  Arc::new(index)
+}
+
+fn lookup_path(db: &dyn SourceDatabase, path: Path) -> Vec<FileId> {
+  db.path_index().lookup(&path).to_vec()
+}
+
+fn file_layers(db: &dyn SourceDatabase, file_id: FileId) -> Vec<FileId> {
+  let index = db.path_index();
+
+  match index.file(file_id) {
+    Some(file) => index.lookup(&file.path).to_vec(),
+    None => vec![file_id],
+  }
+}
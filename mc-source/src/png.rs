@@ -0,0 +1,80 @@
+//! Reads the header of PNG files. We don't need any pixel data, just the size
+//! and format of textures.
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PngHeader {
+  pub width:      u32,
+  pub height:     u32,
+  pub bit_depth:  u8,
+  pub color_type: ColorType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorType {
+  Grayscale,
+  Rgb,
+  Indexed,
+  GrayscaleAlpha,
+  Rgba,
+}
+
+impl PngHeader {
+  /// Parses the `IHDR` chunk, which is always the first chunk in the file.
+  /// Returns `None` if this isn't a valid PNG.
+  pub fn parse(bytes: &[u8]) -> Option<PngHeader> {
+    let rest = bytes.strip_prefix(SIGNATURE)?;
+
+    let length = u32::from_be_bytes(rest.get(0..4)?.try_into().ok()?);
+    if length != 13 || rest.get(4..8)? != b"IHDR" {
+      return None;
+    }
+
+    let data = rest.get(8..21)?;
+    let width = u32::from_be_bytes(data[0..4].try_into().ok()?);
+    let height = u32::from_be_bytes(data[4..8].try_into().ok()?);
+    let bit_depth = data[8];
+    let color_type = match data[9] {
+      0 => ColorType::Grayscale,
+      2 => ColorType::Rgb,
+      3 => ColorType::Indexed,
+      4 => ColorType::GrayscaleAlpha,
+      6 => ColorType::Rgba,
+      _ => return None,
+    };
+
+    if width == 0 || height == 0 {
+      return None;
+    }
+
+    Some(PngHeader { width, height, bit_depth, color_type })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_header() {
+    let mut png = SIGNATURE.to_vec();
+    png.extend_from_slice(&13_u32.to_be_bytes());
+    png.extend_from_slice(b"IHDR");
+    png.extend_from_slice(&16_u32.to_be_bytes());
+    png.extend_from_slice(&32_u32.to_be_bytes());
+    png.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    assert_eq!(
+      PngHeader::parse(&png),
+      Some(PngHeader {
+        width:      16,
+        height:     32,
+        bit_depth:  8,
+        color_type: ColorType::Rgba,
+      })
+    );
+    assert_eq!(PngHeader::parse(&png[..20]), None);
+    assert_eq!(PngHeader::parse(b"{}"), None);
+  }
+}
@@ -0,0 +1,107 @@
+use std::{fmt, str::FromStr};
+
+/// A minecraft release version, like `1.12.2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+  pub major: u32,
+  pub minor: u32,
+  pub patch: u32,
+}
+
+/// Resource pack formats, and the first release that uses them. Formats that
+/// aren't listed were only used by snapshots.
+const PACK_FORMATS: &[(u32, Version)] = &[
+  (1, Version::new(1, 6, 1)),
+  (2, Version::new(1, 9, 0)),
+  (3, Version::new(1, 11, 0)),
+  (4, Version::new(1, 13, 0)),
+  (5, Version::new(1, 15, 0)),
+  (6, Version::new(1, 16, 2)),
+  (7, Version::new(1, 17, 0)),
+  (8, Version::new(1, 18, 0)),
+  (9, Version::new(1, 19, 0)),
+  (12, Version::new(1, 19, 3)),
+  (13, Version::new(1, 19, 4)),
+  (15, Version::new(1, 20, 0)),
+  (18, Version::new(1, 20, 2)),
+  (22, Version::new(1, 20, 3)),
+  (32, Version::new(1, 20, 5)),
+  (34, Version::new(1, 21, 0)),
+  (42, Version::new(1, 21, 2)),
+  (46, Version::new(1, 21, 4)),
+  (55, Version::new(1, 21, 5)),
+  (63, Version::new(1, 21, 6)),
+  (64, Version::new(1, 21, 7)),
+];
+
+impl Version {
+  /// 1.13 removed numeric ids, and changed blockstates and models quite a bit.
+  pub const FLATTENING: Version = Version::new(1, 13, 0);
+
+  /// The first version to support `supported_formats` and overlays in
+  /// `pack.mcmeta`.
+  pub const OVERLAYS: Version = Version::new(1, 20, 2);
+
+  pub const fn new(major: u32, minor: u32, patch: u32) -> Self { Version { major, minor, patch } }
+
+  /// Returns the first release that uses the given pack format. For snapshot
+  /// formats, this returns the release before that snapshot.
+  pub fn from_pack_format(format: u32) -> Option<Version> {
+    PACK_FORMATS.iter().take_while(|(f, _)| *f <= format).last().map(|(_, v)| *v)
+  }
+
+  /// Returns `true` if the given format was used by a release.
+  pub fn is_release_pack_format(format: u32) -> bool {
+    PACK_FORMATS.iter().any(|(f, _)| *f == format)
+  }
+
+  /// Returns the pack format used by this version.
+  pub fn pack_format(&self) -> Option<u32> {
+    PACK_FORMATS.iter().take_while(|(_, v)| v <= self).last().map(|(f, _)| *f)
+  }
+}
+
+impl FromStr for Version {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut parts = s.split('.');
+    let major = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+    let minor = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+    let patch = match parts.next() {
+      Some(p) => p.parse().map_err(|_| ())?,
+      None => 0,
+    };
+    if parts.next().is_some() {
+      return Err(());
+    }
+
+    Ok(Version { major, minor, patch })
+  }
+}
+
+impl fmt::Display for Version {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.patch == 0 {
+      write!(f, "{}.{}", self.major, self.minor)
+    } else {
+      write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn pack_formats() {
+    let v1_12_2: Version = "1.12.2".parse().unwrap();
+    assert_eq!(v1_12_2.pack_format(), Some(3));
+    assert_eq!(Version::from_pack_format(3), Some(Version::new(1, 11, 0)));
+    assert_eq!(Version::from_pack_format(14), Some(Version::new(1, 19, 4)));
+    assert_eq!(Version::from_pack_format(0), None);
+    assert_eq!("1.20".parse(), Ok(Version::new(1, 20, 0)));
+    assert_eq!("1.20-pre1".parse::<Version>(), Err(()));
+  }
+}